@@ -0,0 +1,837 @@
+//! headless command-line tool to import the data and query the gpa ranking without a window
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use neau_gpa_core::db::table::{ResultRow, Transfer};
+use neau_gpa_core::err::ErrorCode;
+use neau_gpa_core::export::{export_rows, ExportFormat};
+use neau_gpa_core::statistics::{summarize, DEFAULT_BINS};
+use neau_gpa_core::{
+    run_import, Aggregation, AppState, Cohort, CustomError, ImportReport, MergeMode,
+    ProgressReporter, RankScope, TermPreset,
+};
+use serde::Serialize;
+use simplelog::{Config, LevelFilter, TermLogger};
+use std::{error::Error, path::PathBuf, process::ExitCode};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Parser)]
+#[command(name = "neau-gpa-cli", version, about = "导入绩点数据并查询排名")]
+struct Cli {
+    /// the path of the database file
+    #[arg(long, env = "NEAU_GPA_DB", default_value = "data.db", global = true)]
+    db: PathBuf,
+    /// print the result as JSON instead of a table
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// import the data directories or `data_yyyy_yyyy.zip` archives
+    Import {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// how to handle the terms that are already loaded
+        #[arg(long, value_enum, default_value_t = Mode::Replace)]
+        mode: Mode,
+    },
+    /// list the loaded terms in chronological order
+    Terms,
+    /// list the colleges
+    Colleges,
+    /// list the majors of the college, given by id or name
+    Majors { college: String },
+    /// list the grades, i.e. the enrollment years, of the major, or of all the majors
+    Grades { major: Option<String> },
+    /// list the classes of the major in the grade, such as 2021
+    Classes { major: String, grade: i32 },
+    /// query the gpa ranking of the grade, within a class, some majors or a college
+    Gpa(GpaArgs),
+    /// show the statistics of the gpa ranking, such as the mean, the quartiles and a histogram
+    Stats {
+        #[command(flatten)]
+        query: GpaArgs,
+        /// the count of the histogram bins between the lowest and the highest gpa
+        #[arg(long, default_value_t = DEFAULT_BINS)]
+        bins: usize,
+    },
+    /// export the gpa ranking to a csv, xlsx or json file
+    Export {
+        #[command(flatten)]
+        query: GpaArgs,
+        /// the path of the exported file
+        #[arg(long, short)]
+        output: PathBuf,
+        /// the format of the file, given by the extension of `--output` if omitted
+        #[arg(long, value_enum)]
+        format: Option<FormatKind>,
+    },
+    /// show the class, major, gpa and rank of each term of the students,
+    /// found by the student number or the name
+    Student { keyword: String },
+    /// list the students whose class or major changed between consecutive terms
+    Transfers {
+        /// only the changes into or out of the major, given by id or name
+        #[arg(long)]
+        major: Option<String>,
+        /// only the changes into or out of the grade, such as 2021
+        #[arg(long)]
+        grade: Option<i32>,
+    },
+}
+
+/// the selection of the gpa ranking, shared by `gpa` and `stats`
+#[derive(Args)]
+struct GpaArgs {
+    /// the term ids or names, separated by commas
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required_unless_present_any = ["academic_year", "since_enrollment", "last"]
+    )]
+    terms: Vec<String>,
+    /// also select the fall and spring terms of the academic year, such as 2023 for 2023-2024
+    #[arg(long, conflicts_with_all = ["since_enrollment", "last"])]
+    academic_year: Option<i32>,
+    /// also select the fall and spring terms since the grade enrolled
+    #[arg(long, conflicts_with = "last")]
+    since_enrollment: bool,
+    /// also select the latest fall and spring terms
+    #[arg(long)]
+    last: Option<u32>,
+    /// the major ids or names, separated by commas; the whole grade is ranked together if
+    /// neither the majors nor the college is given
+    #[arg(long, value_delimiter = ',')]
+    major: Vec<String>,
+    /// the college id or name, all of its majors are ranked together
+    #[arg(long, conflicts_with = "major")]
+    college: Option<String>,
+    /// the grade, i.e. the enrollment year, such as 2021
+    #[arg(long)]
+    grade: i32,
+    /// the class id or name of the major, optional
+    #[arg(long, requires = "major")]
+    class: Option<String>,
+    /// how to aggregate the gpa of several terms
+    #[arg(long, value_enum, default_value_t = AggregationKind::Sum)]
+    aggregation: AggregationKind,
+    /// the weight of each selected term, required by `weighted-mean`; in `--terms` order,
+    /// or in chronological order if the terms are also selected by a preset
+    #[arg(long, value_delimiter = ',')]
+    weights: Vec<f64>,
+    /// which major the students are ranked in
+    #[arg(long, value_enum, default_value_t = CohortKind::LastTerm)]
+    cohort: CohortKind,
+}
+
+/// the format of the exported file
+#[derive(ValueEnum, Clone, Copy)]
+enum FormatKind {
+    /// UTF-8 with BOM, opened correctly by Excel on Chinese Windows
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl From<FormatKind> for ExportFormat {
+    fn from(kind: FormatKind) -> Self {
+        match kind {
+            FormatKind::Csv => ExportFormat::Csv,
+            FormatKind::Xlsx => ExportFormat::Xlsx,
+            FormatKind::Json => ExportFormat::Json,
+        }
+    }
+}
+
+/// how to handle the terms that are already loaded
+#[derive(ValueEnum, Clone, Copy)]
+enum Mode {
+    Replace,
+    Skip,
+}
+
+/// how to aggregate the gpa of several terms
+#[derive(ValueEnum, Clone, Copy)]
+enum AggregationKind {
+    Sum,
+    Mean,
+    WeightedMean,
+}
+
+/// which major the students are ranked in
+#[derive(ValueEnum, Clone, Copy)]
+enum CohortKind {
+    /// the major of the chronologically latest selected term
+    LastTerm,
+    /// the latest major of each student, the students transferred out are not ranked
+    Latest,
+    /// the original major of each student, the students transferred in are not ranked
+    Original,
+}
+
+impl From<CohortKind> for Cohort {
+    fn from(kind: CohortKind) -> Self {
+        match kind {
+            CohortKind::LastTerm => Cohort::LastSelectedTerm,
+            CohortKind::Latest => Cohort::LatestMajor,
+            CohortKind::Original => Cohort::OriginalMajor,
+        }
+    }
+}
+
+impl From<Mode> for MergeMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Replace => MergeMode::Replace,
+            Mode::Skip => MergeMode::Skip,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    TermLogger::init(
+        LevelFilter::Warn,
+        Config::default(),
+        simplelog::TerminalMode::Stderr,
+        simplelog::ColorChoice::Auto,
+    )
+    .expect("Failed to initialize logger");
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            // such as the files that failed to parse
+            if let Some(details) = e
+                .downcast_ref::<CustomError>()
+                .filter(|e| e.code() == ErrorCode::Parse)
+                .and_then(|e| e.details())
+            {
+                eprintln!("{}", details);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let db = AppState::open(&cli.db).await?;
+
+    match cli.command {
+        Command::Import { paths, mode } => {
+            // stop the import on Ctrl-C, nothing is written to the database
+            let cancel = CancellationToken::new();
+            let token = cancel.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    token.cancel();
+                }
+            });
+
+            let report = run_import(
+                &db,
+                paths,
+                mode.into(),
+                &ProgressReporter::default(),
+                &cancel,
+            )
+            .await?;
+            print_report(&report, cli.json)?;
+        }
+        Command::Terms => {
+            let terms = db.get_terms().await?;
+            output(
+                cli.json,
+                &terms,
+                &["id", "学期", "学年", "第几学期"],
+                |term| {
+                    vec![
+                        term.term_id.to_string(),
+                        term.term_name.clone(),
+                        match (term.start_year, term.end_year) {
+                            (Some(start_year), Some(end_year)) => {
+                                format!("{}-{}", start_year, end_year)
+                            }
+                            _ => "-".to_string(),
+                        },
+                        term.semester.map_or("-".to_string(), |s| s.to_string()),
+                    ]
+                },
+            )?;
+        }
+        Command::Colleges => {
+            let colleges = db.get_colleges().await?;
+            output(cli.json, &colleges, &["id", "学院"], |college| {
+                vec![college.college_id.to_string(), college.college_name.clone()]
+            })?;
+        }
+        Command::Majors { college } => {
+            let college_id = find_college(&db, &college).await?;
+            let majors = db.get_majors(college_id).await?;
+            output(cli.json, &majors, &["id", "专业"], |major| {
+                vec![major.major_id.to_string(), major.major_name.clone()]
+            })?;
+        }
+        Command::Grades { major } => {
+            let major_id = match major {
+                Some(major) => Some(find_major(&db, &major).await?),
+                None => None,
+            };
+            let grades = db.get_grades(major_id).await?;
+            output(cli.json, &grades, &["年级"], |grade| {
+                vec![grade.to_string()]
+            })?;
+        }
+        Command::Classes { major, grade } => {
+            let major_id = find_major(&db, &major).await?;
+            let classes = db.get_classes(major_id, grade).await?;
+            output(cli.json, &classes, &["id", "班级"], |class| {
+                vec![class.class_id.to_string(), class.class_name.clone()]
+            })?;
+        }
+        Command::Gpa(query) => {
+            let rows = query_gpa(&db, query).await?;
+            output(
+                cli.json,
+                &rows,
+                &[
+                    "排名",
+                    "学院",
+                    "专业",
+                    "班级",
+                    "学号",
+                    "姓名",
+                    "学分绩",
+                    "学期数",
+                    "百分位",
+                    "转专业",
+                ],
+                |row| {
+                    vec![
+                        format_option(row.competition_rank),
+                        row.college.clone(),
+                        row.major.clone(),
+                        row.class.clone(),
+                        row.sno.clone(),
+                        row.name.clone(),
+                        match (row.gpa, row.incomplete) {
+                            (Some(gpa), true) => format!("{:.2}(学期不完整)", gpa),
+                            (gpa, _) => format_option(gpa.map(|gpa| format!("{:.2}", gpa))),
+                        },
+                        row.gpa_terms.to_string(),
+                        format_option(row.percentile.map(|p| format!("{:.1}%", p))),
+                        match (row.transfer, &row.transfer_major) {
+                            (Some(Transfer::In), Some(major)) => format!("由{}转入", major),
+                            (Some(Transfer::Out), Some(major)) => format!("转出至{}", major),
+                            _ => "-".to_string(),
+                        },
+                    ]
+                },
+            )?;
+        }
+        Command::Stats { query, bins } => {
+            let rows = query_gpa(&db, query).await?;
+            let statistics = summarize(&rows, bins)?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&statistics)?);
+                return Ok(());
+            }
+            let format_gpa = |gpa: Option<f64>| format_option(gpa.map(|gpa| format!("{:.2}", gpa)));
+            print_table(
+                &[
+                    "人数",
+                    "缺失",
+                    "平均",
+                    "中位数",
+                    "标准差",
+                    "下四分位",
+                    "上四分位",
+                    "最低",
+                    "最高",
+                ],
+                vec![vec![
+                    statistics.count.to_string(),
+                    statistics.missing.to_string(),
+                    format_gpa(statistics.mean),
+                    format_gpa(statistics.median),
+                    format_gpa(statistics.std_dev),
+                    format_gpa(statistics.q1),
+                    format_gpa(statistics.q3),
+                    format_gpa(statistics.min),
+                    format_gpa(statistics.max),
+                ]],
+            );
+            println!();
+            print_table(
+                &["分组", "人数"],
+                statistics
+                    .histogram
+                    .iter()
+                    .map(|bin| {
+                        vec![
+                            format!("{:.2} - {:.2}", bin.lower, bin.upper),
+                            bin.count.to_string(),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        Command::Export {
+            query,
+            output,
+            format,
+        } => {
+            let format = match format {
+                Some(format) => format.into(),
+                None => ExportFormat::from_path(&output)
+                    .ok_or("无法从文件扩展名确定导出格式, 请使用 --format 指定")?,
+            };
+            let rows = query_gpa(&db, query).await?;
+            export_rows(&rows, &output, format)?;
+            println!("已导出{}名学生至{}", rows.len(), output.display());
+        }
+        Command::Student { keyword } => {
+            let histories = db.get_student_history(&keyword).await?;
+            if histories.is_empty() {
+                return Err(format!("未找到学生: {}", keyword).into());
+            }
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&histories)?);
+                return Ok(());
+            }
+            for history in &histories {
+                println!(
+                    "{} {}",
+                    history.student.student_number, history.student.name
+                );
+                print_table(
+                    &[
+                        "学期",
+                        "学院",
+                        "专业",
+                        "班级",
+                        "学分绩",
+                        "班级排名",
+                        "专业排名",
+                    ],
+                    history
+                        .terms
+                        .iter()
+                        .map(|term| {
+                            vec![
+                                term.term_name.clone(),
+                                term.college_name.clone(),
+                                term.major_name.clone(),
+                                term.class_name.clone(),
+                                format_option(term.gpa.map(|gpa| format!("{:.2}", gpa))),
+                                format_rank(term.class_rank, term.class_size),
+                                format_rank(term.major_rank, term.major_size),
+                            ]
+                        })
+                        .collect(),
+                );
+                println!();
+            }
+        }
+        Command::Transfers { major, grade } => {
+            let major_id = match major {
+                Some(major) => Some(find_major(&db, &major).await?),
+                None => None,
+            };
+            let changes = db.get_class_changes(major_id, grade).await?;
+            output(
+                cli.json,
+                &changes,
+                &[
+                    "学期",
+                    "学号",
+                    "姓名",
+                    "原班级",
+                    "现班级",
+                    "原专业",
+                    "现专业",
+                ],
+                |change| {
+                    vec![
+                        change.term_name.clone(),
+                        change.student_number.clone(),
+                        change.name.clone(),
+                        change.from_class.clone(),
+                        change.to_class.clone(),
+                        change.from_major.clone(),
+                        change.to_major.clone(),
+                    ]
+                },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// resolve the terms and the scope of the selection and query the gpa ranking
+async fn query_gpa(db: &AppState, query: GpaArgs) -> Result<Vec<ResultRow>, Box<dyn Error>> {
+    let GpaArgs {
+        terms,
+        academic_year,
+        since_enrollment,
+        last,
+        major,
+        college,
+        grade,
+        class,
+        aggregation,
+        weights,
+        cohort,
+    } = query;
+    let loaded_terms = db.get_terms().await?;
+    let term_ids = terms
+        .iter()
+        .map(|term| {
+            resolve_id(
+                term,
+                "学期",
+                loaded_terms
+                    .iter()
+                    .map(|t| (t.term_id, t.term_name.as_str())),
+            )
+        })
+        .collect::<Result<Vec<i64>, String>>()?;
+    let preset = match (academic_year, since_enrollment, last) {
+        (Some(year), _, _) => Some(TermPreset::AcademicYear { year }),
+        (_, true, _) => Some(TermPreset::SinceEnrollment),
+        (_, _, Some(count)) => Some(TermPreset::LastTerms { count }),
+        _ => None,
+    };
+    let term_ids = db.resolve_terms(&term_ids, preset, Some(grade)).await?;
+    let aggregation = aggregation_of(aggregation, weights, &term_ids)?;
+    let scope = resolve_scope(db, &major, college, class, grade).await?;
+
+    Ok(db
+        .get_gpa(&term_ids, &scope, grade, &aggregation, cohort.into())
+        .await?)
+}
+
+/// the aggregation of the terms, the weights are given to the terms in order
+fn aggregation_of(
+    kind: AggregationKind,
+    weights: Vec<f64>,
+    term_ids: &[i64],
+) -> Result<Aggregation, String> {
+    Ok(match kind {
+        AggregationKind::Sum => Aggregation::Sum,
+        AggregationKind::Mean => Aggregation::Mean,
+        AggregationKind::WeightedMean => {
+            if weights.len() != term_ids.len() {
+                return Err("每个学期都需要一个权重".to_string());
+            }
+            Aggregation::WeightedMean {
+                weights: term_ids.iter().copied().zip(weights).collect(),
+            }
+        }
+    })
+}
+
+/// the students ranked together: a class of the major, the majors, the college or the grade
+async fn resolve_scope(
+    db: &AppState,
+    major: &[String],
+    college: Option<String>,
+    class: Option<String>,
+    grade: i32,
+) -> Result<RankScope, Box<dyn Error>> {
+    Ok(match (class, college, major) {
+        (Some(class), _, [major]) => {
+            let classes = db.get_classes(find_major(db, major).await?, grade).await?;
+            RankScope::Class {
+                class_id: resolve_id(
+                    &class,
+                    "班级",
+                    classes.iter().map(|c| (c.class_id, c.class_name.as_str())),
+                )?,
+            }
+        }
+        (Some(_), _, _) => return Err("按班级排名时只能指定一个专业".into()),
+        (None, Some(college), _) => RankScope::College {
+            college_id: find_college(db, &college).await?,
+        },
+        (None, None, []) => RankScope::Grade,
+        (None, None, [major]) => RankScope::Major {
+            major_id: find_major(db, major).await?,
+        },
+        (None, None, majors) => {
+            let mut major_ids = Vec::new();
+            for major in majors {
+                major_ids.push(find_major(db, major).await?);
+            }
+            RankScope::Majors { major_ids }
+        }
+    })
+}
+
+/// find the college id by id or name
+async fn find_college(db: &AppState, college: &str) -> Result<i64, Box<dyn Error>> {
+    let colleges = db.get_colleges().await?;
+    Ok(resolve_id(
+        college,
+        "学院",
+        colleges
+            .iter()
+            .map(|c| (c.college_id, c.college_name.as_str())),
+    )?)
+}
+
+/// find the major id by id or name among all the colleges
+async fn find_major(db: &AppState, major: &str) -> Result<i64, Box<dyn Error>> {
+    let mut majors = Vec::new();
+    for college in db.get_colleges().await? {
+        majors.extend(db.get_majors(college.college_id).await?);
+    }
+    Ok(resolve_id(
+        major,
+        "专业",
+        majors.iter().map(|m| (m.major_id, m.major_name.as_str())),
+    )?)
+}
+
+/// resolve the id of the item given by id or name, the name must be unique
+fn resolve_id<'a>(
+    value: &str,
+    kind: &str,
+    items: impl IntoIterator<Item = (i64, &'a str)>,
+) -> Result<i64, String> {
+    if let Ok(id) = value.parse::<i64>() {
+        return Ok(id);
+    }
+    // the same major name may exist in several colleges
+    let ids: Vec<i64> = items
+        .into_iter()
+        .filter(|(_, name)| *name == value)
+        .map(|(id, _)| id)
+        .collect();
+    match ids[..] {
+        [id] => Ok(id),
+        [] => Err(format!("未找到{}: {}", kind, value)),
+        _ => Err(format!("{}名称不唯一, 请使用id: {}", kind, value)),
+    }
+}
+
+/// print the import report as a summary of each term
+fn print_report(report: &ImportReport, json: bool) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+        return Ok(());
+    }
+    print_table(
+        &["学期", "学院数", "记录数"],
+        report
+            .terms
+            .iter()
+            .map(|term| {
+                vec![
+                    term.term_name.clone(),
+                    term.colleges.len().to_string(),
+                    term.rows.to_string(),
+                ]
+            })
+            .collect(),
+    );
+    if !report.skipped_terms.is_empty() {
+        println!("跳过已导入的学期: {}", report.skipped_terms.join(", "));
+    }
+    Ok(())
+}
+
+/// print the items as JSON or as a table
+fn output<T: Serialize>(
+    json: bool,
+    items: &[T],
+    header: &[&str],
+    row: impl Fn(&T) -> Vec<String>,
+) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(items)?);
+    } else {
+        print_table(header, items.iter().map(row).collect());
+    }
+    Ok(())
+}
+
+/// format the optional value, the missing value is shown as `-`
+fn format_option<T: ToString>(value: Option<T>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+/// format the rank as `rank/size`, the missing rank is shown as `-`
+fn format_rank(rank: Option<i64>, size: i64) -> String {
+    format!("{}/{}", format_option(rank), size)
+}
+
+/// print the rows as a table with aligned columns
+fn print_table(header: &[&str], rows: Vec<Vec<String>>) {
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let mut widths: Vec<usize> = header.iter().map(|h| display_width(h)).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| {
+                let padding = width - display_width(cell);
+                format!("{}{}", cell, " ".repeat(padding))
+            })
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+/// the width of the text in a terminal, the CJK characters take two columns
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c as u32 >= 0x1100 { 2 } else { 1 })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// import a term with the classes of the majors `major` and `math` in one college
+    async fn build_db(root: &std::path::Path) -> AppState {
+        let college_dir = root
+            .join("2021-2022-1")
+            .join("2021-2022-1学期智育学分绩")
+            .join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+        for (file, sno) in [
+            ("a21major2101hz.csv", "2021001"),
+            ("a21major2102hz.csv", "2021002"),
+            ("a21math2101hz.csv", "2021003"),
+        ] {
+            fs::write(
+                college_dir.join(file),
+                format!(
+                    "xh,xm,k101\n,,00231|0.0|2021-2022-1智育学分绩||\n{},{},85.5\n",
+                    sno, sno
+                ),
+            )
+            .unwrap();
+        }
+        let db = AppState::open(&root.join("data.db")).await.unwrap();
+        run_import(
+            &db,
+            vec![root.join("2021-2022-1")],
+            MergeMode::Replace,
+            &ProgressReporter::default(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_resolve_id() {
+        let items = [(1, "math"), (2, "major"), (3, "major")];
+        assert_eq!(resolve_id("7", "专业", items), Ok(7));
+        assert_eq!(resolve_id("math", "专业", items), Ok(1));
+        assert_eq!(
+            resolve_id("physics", "专业", items),
+            Err("未找到专业: physics".to_string())
+        );
+        assert_eq!(
+            resolve_id("major", "专业", items),
+            Err("专业名称不唯一, 请使用id: major".to_string())
+        );
+    }
+
+    #[test]
+    fn test_weights_must_match_terms() {
+        let result = aggregation_of(AggregationKind::WeightedMean, vec![1.0], &[1, 2]);
+        assert_eq!(result, Err("每个学期都需要一个权重".to_string()));
+
+        let aggregation =
+            aggregation_of(AggregationKind::WeightedMean, vec![1.0, 3.0], &[1, 2]).unwrap();
+        let Aggregation::WeightedMean { weights } = aggregation else {
+            panic!("not a weighted mean");
+        };
+        assert_eq!(weights[&2], 3.0);
+        // the weights are ignored by the other aggregations
+        assert_eq!(
+            aggregation_of(AggregationKind::Mean, vec![1.0], &[1, 2]),
+            Ok(Aggregation::Mean)
+        );
+    }
+
+    #[test]
+    fn test_scope_flags_conflict() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(
+                ["neau-gpa-cli", "gpa", "--terms", "1", "--grade", "2021"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+        assert!(parse(&[]).is_ok());
+        assert!(parse(&["--major", "1,2"]).is_ok());
+        assert!(parse(&["--college", "1", "--major", "1"]).is_err());
+        assert!(parse(&["--class", "1"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_scope() {
+        let temp_dir = tempdir().unwrap();
+        let db = build_db(temp_dir.path()).await;
+        let majors = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let scope = |major: Vec<String>, college: Option<&str>, class: Option<&str>| {
+            let db = db.clone();
+            let (college, class) = (college.map(String::from), class.map(String::from));
+            async move {
+                resolve_scope(&db, &major, college, class, 2021)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        };
+        let major_id = find_major(&db, "major").await.unwrap();
+        let math_id = find_major(&db, "math").await.unwrap();
+
+        assert_eq!(scope(vec![], None, None).await, Ok(RankScope::Grade));
+        assert_eq!(
+            scope(majors(&["major"]), None, None).await,
+            Ok(RankScope::Major { major_id })
+        );
+        assert_eq!(
+            scope(majors(&["major", "math"]), None, None).await,
+            Ok(RankScope::Majors {
+                major_ids: vec![major_id, math_id]
+            })
+        );
+        assert!(matches!(
+            scope(vec![], Some("College"), None).await,
+            Ok(RankScope::College { .. })
+        ));
+        let class_id = db.get_classes(major_id, 2021).await.unwrap()[1].class_id;
+        assert_eq!(
+            scope(majors(&["major"]), None, Some("major2102")).await,
+            Ok(RankScope::Class { class_id })
+        );
+        assert_eq!(
+            scope(majors(&["major", "math"]), None, Some("major2102")).await,
+            Err("按班级排名时只能指定一个专业".to_string())
+        );
+        assert_eq!(
+            scope(majors(&["physics"]), None, None).await,
+            Err("未找到专业: physics".to_string())
+        );
+    }
+}
@@ -0,0 +1,15 @@
+-- 删除重复导入的成绩记录, 每个学生每学期仅保留最后导入的一条
+DELETE FROM academic_records
+WHERE
+    record_id NOT IN (
+        SELECT
+            MAX(record_id)
+        FROM
+            academic_records
+        GROUP BY
+            student_id,
+            term_id
+    );
+
+-- 每个学生每学期只能有一条成绩记录
+CREATE UNIQUE INDEX IF NOT EXISTS idx_student_term ON academic_records (student_id, term_id);
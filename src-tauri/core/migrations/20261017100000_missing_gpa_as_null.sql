@@ -0,0 +1,7 @@
+-- 旧版本将缺失的学分绩写为0, 改为NULL以免缺失的学分绩被当作0参与排名
+-- 学分绩恰为0与缺失无法区分, 均视为缺失; 重新导入对应学期即可恢复原始数据
+UPDATE academic_records
+SET
+    gpa = NULL
+WHERE
+    gpa = 0;
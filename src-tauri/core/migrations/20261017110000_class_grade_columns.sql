@@ -0,0 +1,16 @@
+-- 班级的入学年份与班号, 如"计算机科学2101"为2021级1班
+-- 此前按班级名称末尾的数字模糊匹配年级, 改为结构化的列
+ALTER TABLE classes ADD COLUMN enrollment_year INTEGER;
+
+ALTER TABLE classes ADD COLUMN class_number INTEGER;
+
+-- 回填已导入的班级, 班级名称末尾不是四位数字的保持为NULL
+UPDATE classes
+SET
+    enrollment_year = 2000 + CAST(substr(class_name, -4, 2) AS INTEGER),
+    class_number = CAST(substr(class_name, -2) AS INTEGER)
+WHERE
+    class_name GLOB '*[0-9][0-9][0-9][0-9]';
+
+-- 按专业与年级查询班级
+CREATE INDEX IF NOT EXISTS idx_major_enrollment_year ON classes (major_id, enrollment_year);
@@ -0,0 +1,95 @@
+-- 专业在学院内唯一, 班级在专业内唯一, 此前专业与班级的名称全局唯一
+-- 不同学院的同名专业(或调整了所属学院的专业)会被合并到先导入的学院下, 其班级与成绩记录也随之合并
+-- 数据库中没有记录成绩来自哪个学院, 已合并的记录无法在迁移中区分, 这里只保留原有的id重建约束;
+-- 以覆盖模式重新导入对应的学期即可拆分: 成绩记录按(学院, 专业, 班级)关联到各自的班级,
+-- 不再有成绩记录的旧班级与专业在导入时删除
+-- SQLite不能删除列上的UNIQUE约束, 因此重建三张表; 先建新表, 再从子表开始删除旧表, 外键始终有效
+CREATE TABLE
+    majors_scoped (
+        major_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        major_name TEXT NOT NULL,
+        college_id INTEGER NOT NULL,
+        FOREIGN KEY (college_id) REFERENCES colleges (college_id),
+        UNIQUE (college_id, major_name)
+    );
+
+INSERT INTO
+    majors_scoped (major_id, major_name, college_id)
+SELECT
+    major_id,
+    major_name,
+    college_id
+FROM
+    majors;
+
+CREATE TABLE
+    classes_scoped (
+        class_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        class_name TEXT NOT NULL,
+        major_id INTEGER NOT NULL,
+        enrollment_year INTEGER,
+        class_number INTEGER,
+        FOREIGN KEY (major_id) REFERENCES majors_scoped (major_id),
+        UNIQUE (major_id, class_name)
+    );
+
+INSERT INTO
+    classes_scoped (
+        class_id,
+        class_name,
+        major_id,
+        enrollment_year,
+        class_number
+    )
+SELECT
+    class_id,
+    class_name,
+    major_id,
+    enrollment_year,
+    class_number
+FROM
+    classes;
+
+CREATE TABLE
+    academic_records_scoped (
+        record_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        student_id INTEGER NOT NULL,
+        term_id INTEGER NOT NULL,
+        class_id INTEGER NOT NULL,
+        gpa REAL,
+        FOREIGN KEY (student_id) REFERENCES students (student_id),
+        FOREIGN KEY (term_id) REFERENCES terms (term_id),
+        FOREIGN KEY (class_id) REFERENCES classes_scoped (class_id)
+    );
+
+INSERT INTO
+    academic_records_scoped (record_id, student_id, term_id, class_id, gpa)
+SELECT
+    record_id,
+    student_id,
+    term_id,
+    class_id,
+    gpa
+FROM
+    academic_records;
+
+DROP TABLE academic_records;
+
+DROP TABLE classes;
+
+DROP TABLE majors;
+
+-- 重命名时其他表外键中的表名随之更新
+ALTER TABLE majors_scoped
+RENAME TO majors;
+
+ALTER TABLE classes_scoped
+RENAME TO classes;
+
+ALTER TABLE academic_records_scoped
+RENAME TO academic_records;
+
+-- 重建随旧表删除的索引
+CREATE UNIQUE INDEX IF NOT EXISTS idx_student_term ON academic_records (student_id, term_id);
+
+CREATE INDEX IF NOT EXISTS idx_major_enrollment_year ON classes (major_id, enrollment_year);
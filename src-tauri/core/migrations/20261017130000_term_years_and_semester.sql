@@ -0,0 +1,18 @@
+-- 学期的学年与学期序号, 如"2022-2023-3"为2022-2023学年的第3学期(夏季学期)
+-- 学期按(开始年份, 学期序号)排序, 而不是按名称字符串
+ALTER TABLE terms ADD COLUMN start_year INTEGER;
+
+ALTER TABLE terms ADD COLUMN end_year INTEGER;
+
+ALTER TABLE terms ADD COLUMN semester INTEGER;
+
+-- 回填已导入的学期, 导入时学期名称已校验过格式
+UPDATE terms
+SET
+    start_year = CAST(substr(term_name, 1, 4) AS INTEGER),
+    end_year = CAST(substr(term_name, 6, 4) AS INTEGER),
+    semester = CAST(substr(term_name, 11, 1) AS INTEGER)
+WHERE
+    term_name GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9][0-9][0-9]-[0-9]';
+
+CREATE INDEX IF NOT EXISTS idx_term_order ON terms (start_year, semester);
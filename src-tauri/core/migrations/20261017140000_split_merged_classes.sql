@@ -0,0 +1,95 @@
+-- 旧版本中专业与班级的名称全局唯一, 不同学院的同名专业(或调整了所属学院的专业)被合并到先导入的学院下
+-- 数据库中没有记录成绩来自哪个学院, 只能由学生在其他学期的记录推断:
+-- 若一个班级的所有学生在其他班级的记录都属于同一个其他学院, 且所在专业还有其他班级(即专业混有多个学院的班级),
+-- 该班级应属于那个学院, 将其移到那个学院的同名专业下(没有则新建)
+-- 专业的班级都指向其他学院时, 无法判断是该专业被合并还是证据所在的班级被合并, 与只有部分学生属于其他学院的班级一样,
+-- 由程序在打开数据库与导入页面中提示
+CREATE TEMPORARY TABLE misplaced_classes AS
+WITH
+    class_students AS (
+        SELECT
+            class_id,
+            COUNT(DISTINCT student_id) AS students
+        FROM
+            academic_records
+        GROUP BY
+            class_id
+    ),
+    -- 每个班级的学生在其他班级的记录所属的学院
+    outside AS (
+        SELECT DISTINCT
+            own.class_id,
+            own.student_id,
+            other_majors.college_id
+        FROM
+            academic_records AS own
+            JOIN academic_records AS other ON other.student_id = own.student_id
+            AND other.class_id <> own.class_id
+            JOIN classes AS other_classes ON other_classes.class_id = other.class_id
+            JOIN majors AS other_majors ON other_majors.major_id = other_classes.major_id
+    )
+SELECT
+    classes.class_id,
+    classes.major_id AS old_major_id,
+    majors.major_name,
+    MIN(outside.college_id) AS college_id
+FROM
+    classes
+    JOIN majors ON majors.major_id = classes.major_id
+    JOIN class_students ON class_students.class_id = classes.class_id
+    JOIN outside ON outside.class_id = classes.class_id
+GROUP BY
+    classes.class_id
+HAVING
+    COUNT(DISTINCT outside.college_id) = 1
+    AND MIN(outside.college_id) <> MIN(majors.college_id)
+    AND COUNT(DISTINCT outside.student_id) = MIN(class_students.students);
+
+-- 专业的班级全部指向其他学院时无法确定, 保留原样
+DELETE FROM misplaced_classes
+WHERE
+    old_major_id NOT IN (
+        SELECT
+            classes.major_id
+        FROM
+            classes
+        WHERE
+            classes.class_id NOT IN (
+                SELECT
+                    class_id
+                FROM
+                    misplaced_classes
+            )
+    );
+
+INSERT
+OR IGNORE INTO majors (major_name, college_id)
+SELECT DISTINCT
+    major_name,
+    college_id
+FROM
+    misplaced_classes;
+
+-- 目标专业下已有同名班级时保留原样, 留待提示
+UPDATE
+OR IGNORE classes
+SET
+    major_id = (
+        SELECT
+            majors.major_id
+        FROM
+            misplaced_classes
+            JOIN majors ON majors.major_name = misplaced_classes.major_name
+            AND majors.college_id = misplaced_classes.college_id
+        WHERE
+            misplaced_classes.class_id = classes.class_id
+    )
+WHERE
+    class_id IN (
+        SELECT
+            class_id
+        FROM
+            misplaced_classes
+    );
+
+DROP TABLE misplaced_classes;
@@ -1,7 +1,7 @@
 use csv;
 use regex::Regex;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::{io::Read, path::PathBuf};
 
 use super::err::CustomError;
 
@@ -27,6 +27,20 @@ pub struct CsvTable {
     pub records: CsvRecords,
     pub major_name: String,
     pub class_name: String,
+    // 入学年份, 如2021
+    pub enrollment_year: i32,
+    // 班号, 如2101班为1
+    pub class_number: i32,
+    // 是否有绩点列
+    pub has_gpa_column: bool,
+}
+
+// 从文件名中提取的专业与班级信息
+struct ClassFileInfo {
+    major_name: String,
+    class_name: String,
+    enrollment_year: i32,
+    class_number: i32,
 }
 
 // csv表构建器
@@ -40,17 +54,46 @@ impl<'builder> CsvTableBuilder<'builder> {
     }
 
     pub fn build(&self) -> Result<CsvTable, CustomError> {
-        let (major_name, class_name) = self.extract_major_and_class_info()?;
-        let records = self.build_csv_records()?;
+        let ClassFileInfo {
+            major_name,
+            class_name,
+            enrollment_year,
+            class_number,
+        } = self.extract_major_and_class_info()?;
+        let (records, has_gpa_column) = self.build_csv_records()?;
+
+        Ok(CsvTable {
+            records,
+            major_name,
+            class_name,
+            enrollment_year,
+            class_number,
+            has_gpa_column,
+        })
+    }
+
+    /// 从给定的reader中构建csv表,用于读取压缩包中的csv文件
+    /// 专业和班级信息仍从`csv_path`的文件名中提取
+    pub fn build_from_reader<R: Read>(&self, reader: R) -> Result<CsvTable, CustomError> {
+        let ClassFileInfo {
+            major_name,
+            class_name,
+            enrollment_year,
+            class_number,
+        } = self.extract_major_and_class_info()?;
+        let (records, has_gpa_column) = self.read_csv_records(reader)?;
 
         Ok(CsvTable {
             records,
             major_name,
             class_name,
+            enrollment_year,
+            class_number,
+            has_gpa_column,
         })
     }
 
-    /// 从csv文件中构建记录
+    /// 从csv文件中构建记录, 并返回是否有绩点列
     /// 如果csv文件中没有绩点列，则绩点为None
     ///
     /// # Errors
@@ -59,10 +102,15 @@ impl<'builder> CsvTableBuilder<'builder> {
     /// 如果文件中的数据不符合预期，返回`CustomError::CsvDataError`
     /// 如果文件名不符合规范，返回`CustomError::IllegalFileError`
     /// 如果csv解析失败，返回`CustomError::CsvParseError`
-    fn build_csv_records(&self) -> Result<CsvRecords, CustomError> {
+    fn build_csv_records(&self) -> Result<(CsvRecords, bool), CustomError> {
         let file = std::fs::File::open(self.csv_path)?;
+        self.read_csv_records(file)
+    }
+
+    /// 从reader中读取csv记录,规则同`build_csv_records`
+    fn read_csv_records<R: Read>(&self, reader: R) -> Result<(CsvRecords, bool), CustomError> {
         let mut records: CsvRecords = vec![];
-        let mut rdr = csv::Reader::from_reader(file);
+        let mut rdr = csv::Reader::from_reader(reader);
 
         // 特判空文件
         let header_record = rdr.records().next();
@@ -110,7 +158,7 @@ impl<'builder> CsvTableBuilder<'builder> {
                 }
             }
         }
-        Ok(records)
+        Ok((records, has_valid_gpa))
     }
 
     /// 从文件名中提取专业和班级信息
@@ -120,15 +168,24 @@ impl<'builder> CsvTableBuilder<'builder> {
     /// 如果文件名不符合规范，返回`CustomError::IllegalFileError`
     /// regex构建失败或解析失败，返回`CustomError::RegexError`
     /// 如果出现了预期外的文件，返回`CustomError::UnexpectedFileError`
-    fn extract_major_and_class_info(&self) -> Result<(String, String), CustomError> {
-        let re = Regex::new(r"^[a-z](\d{2})((\D*)\d{4})hz.csv$")?;
+    fn extract_major_and_class_info(&self) -> Result<ClassFileInfo, CustomError> {
+        let re = Regex::new(r"^[a-z](\d{2})((\D*)(\d{2})(\d{2}))hz.csv$")?;
         let file_name = get_file_name(self.csv_path)?;
         if re.is_match(file_name) {
             let captures = re.captures(file_name).expect("Regex match failed"); // safe to unwrap
             let class_name = captures.get(2).map_or("", |m| m.as_str()).to_string();
             let major_name = captures.get(3).map_or("", |m| m.as_str()).to_string();
-
-            Ok((major_name, class_name))
+            // 班级名称末尾的四位数字为入学年份的后两位与班号
+            let number = |i: usize| captures[i].parse::<i32>().expect("Regex match failed"); // two digits
+            let enrollment_year = 2000 + number(4);
+            let class_number = number(5);
+
+            Ok(ClassFileInfo {
+                major_name,
+                class_name,
+                enrollment_year,
+                class_number,
+            })
         } else {
             Err(CustomError::UnexpectedFileError(file_name.to_string()))
         }
@@ -170,15 +227,13 @@ mod tests {
         let file_path = temp_dir.path().join("a22major2012hz.csv");
         File::create(&file_path).unwrap();
 
-        let builder = CsvTableBuilder::new(
-            Arc::new("2022-2023-1".to_string()),
-            Arc::new("Engineering".to_string()),
-            &file_path,
-        );
+        let builder = CsvTableBuilder::new(&file_path);
 
-        let (major, class) = builder.extract_major_and_class_info().unwrap();
-        assert_eq!(major, "major");
-        assert_eq!(class, "major2012");
+        let info = builder.extract_major_and_class_info().unwrap();
+        assert_eq!(info.major_name, "major");
+        assert_eq!(info.class_name, "major2012");
+        assert_eq!(info.enrollment_year, 2020);
+        assert_eq!(info.class_number, 12);
     }
 
     #[test]
@@ -187,11 +242,7 @@ mod tests {
         let file_path = temp_dir.path().join("invalid_filename.csv");
         File::create(&file_path).unwrap();
 
-        let builder = CsvTableBuilder::new(
-            Arc::new("2022".to_string()),
-            Arc::new("Engineering".to_string()),
-            &file_path,
-        );
+        let builder = CsvTableBuilder::new(&file_path);
 
         let result = builder.extract_major_and_class_info();
         assert!(result.is_err());
@@ -213,13 +264,10 @@ mod tests {
         writeln!(file, ",,00231|0.0|2022-2023-1智育学分绩||").unwrap();
         writeln!(file, "12345,John Doe,3.5").unwrap();
 
-        let builder = CsvTableBuilder::new(
-            Arc::new("2022".to_string()),
-            Arc::new("Engineering".to_string()),
-            &file_path,
-        );
+        let builder = CsvTableBuilder::new(&file_path);
 
-        let records = builder.build_csv_records().unwrap();
+        let (records, has_gpa_column) = builder.build_csv_records().unwrap();
+        assert!(has_gpa_column);
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].sid, "12345");
         assert_eq!(records[0].name, "John Doe");
@@ -235,19 +283,29 @@ mod tests {
         writeln!(file, ",,00231|0.0|2022-2023-1智育学分绩||").unwrap();
         writeln!(file, "12345,John Doe,").unwrap();
 
-        let builder = CsvTableBuilder::new(
-            Arc::new("2022".to_string()),
-            Arc::new("Engineering".to_string()),
-            &file_path,
-        );
+        let builder = CsvTableBuilder::new(&file_path);
 
-        let records = builder.build_csv_records().unwrap();
+        let (records, _) = builder.build_csv_records().unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].sid, "12345");
         assert_eq!(records[0].name, "John Doe");
         assert_eq!(records[0].gpa, None);
     }
 
+    #[test]
+    fn test_build_from_reader() {
+        let file_path = PathBuf::from("学期智育学分绩/01College/a22major2012hz.csv");
+        let content = "xh,xm,k101\n,,00231|0.0|2022-2023-1智育学分绩||\n12345,John Doe,3.5\n";
+
+        let table = CsvTableBuilder::new(&file_path)
+            .build_from_reader(content.as_bytes())
+            .unwrap();
+        assert_eq!(table.major_name, "major");
+        assert_eq!(table.class_name, "major2012");
+        assert_eq!(table.records.len(), 1);
+        assert_eq!(table.records[0].gpa, Some(3.5));
+    }
+
     #[test]
     fn test_check_gpa_column_valid() {
         let record = csv::StringRecord::from(vec!["xh", "xm", "00101|3.5|2022-2023-1智育学分绩||"]);
@@ -269,11 +327,7 @@ mod tests {
     #[test]
     fn test_build_csv_records_file_read_error() {
         let csv_path = PathBuf::from("non_existent_file.csv");
-        let builder = CsvTableBuilder::new(
-            Arc::new("2022".to_string()),
-            Arc::new("Engineering".to_string()),
-            &csv_path,
-        );
+        let builder = CsvTableBuilder::new(&csv_path);
 
         let result = builder.build_csv_records();
         assert!(matches!(result, Err(CustomError::FileReadError(_))));
@@ -286,11 +340,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "invalid_csv_content").unwrap();
 
-        let builder = CsvTableBuilder::new(
-            Arc::new("2022".to_string()),
-            Arc::new("Engineering".to_string()),
-            &file_path,
-        );
+        let builder = CsvTableBuilder::new(&file_path);
 
         let result = builder.build_csv_records();
         assert!(matches!(result, Err(CustomError::CsvDataError(_))));
@@ -0,0 +1,769 @@
+use super::csv_processor::{self, CsvTable};
+use crate::csv_processor::get_file_name;
+use crate::err::CustomError;
+use crate::progress::ProgressReporter;
+use crate::report::{DirectoryReport, FileReport, ImportOutcome, ImportReport};
+use crate::term::Term;
+use futures::future::join_all;
+use log::info;
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio_util::sync::CancellationToken;
+
+/// 学期目录名称, 如`2022-2023-1学期智育学分绩`, 第3学期为夏季学期
+const TERM_DIR_PATTERN: &str = r"^\d{4}-\d{4}-[123]学期智育学分绩$";
+/// 学院目录名称, 如`01农学院`
+const COLLEGE_DIR_PATTERN: &str = r"^\d{2}.{2,10}$";
+/// 班级成绩csv文件名称, 如`a22农学2201hz.csv`
+const CSV_FILE_PATTERN: &str = r"^[a-z]\d{2}(\D*)(\d{4})hz.csv$";
+
+pub struct CollegeData {
+    pub term_name: Arc<String>,
+    pub college_name: Arc<String>,
+    pub college_number: Arc<String>,
+    pub data: Vec<CsvTable>,
+}
+
+pub struct DataProducer {
+    tx: tokio::sync::mpsc::Sender<CollegeData>,
+    progress: ProgressReporter,
+    cancel: CancellationToken,
+}
+
+pub struct DataConsumer {
+    rx: tokio::sync::mpsc::Receiver<CollegeData>,
+    progress: ProgressReporter,
+}
+
+impl DataConsumer {
+    pub fn new(rx: tokio::sync::mpsc::Receiver<CollegeData>) -> Self {
+        Self {
+            rx,
+            progress: ProgressReporter::default(),
+        }
+    }
+
+    /// 汇报每个已解析学院的进度
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub async fn consume(&mut self) -> Vec<CollegeData> {
+        let mut result = Vec::with_capacity(150);
+        while let Some(college_data) = self.rx.recv().await {
+            self.progress.college_parsed(
+                &college_data.term_name,
+                &college_data.college_name,
+                college_data.data.len(),
+            );
+            result.push(college_data);
+        }
+        result
+    }
+}
+
+impl DataProducer {
+    pub fn new(tx: tokio::sync::mpsc::Sender<CollegeData>) -> Self {
+        Self {
+            tx,
+            progress: ProgressReporter::default(),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// 汇报发现的学院目录数量
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// 令牌被取消时停止解析, `produce`返回`CustomError::Cancelled`
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// 解析数据目录或`data_yyyy_yyyy.zip`压缩包, 并将每个学院的数据发送给消费者
+    ///
+    /// 返回每个学院目录与csv文件的解析结果
+    pub async fn produce(&self, path: PathBuf) -> Result<ImportReport, CustomError> {
+        if is_zip_file(&path) {
+            return self.produce_from_zip(path).await;
+        }
+
+        let re = Regex::new(COLLEGE_DIR_PATTERN)?;
+        let mut college_dirs = Vec::new();
+        let mut skipped_dirs = Vec::new();
+        collect_college_dirs(&path, &re, &mut college_dirs, &mut skipped_dirs)?;
+        self.progress.dirs_discovered(college_dirs.len());
+
+        let mut report = ImportReport::default();
+        for dir in skipped_dirs {
+            report.directories.push(DirectoryReport {
+                path: dir.to_string_lossy().to_string(),
+                term_name: None,
+                college_name: None,
+                outcome: ImportOutcome::Skipped {
+                    reason: "目录名称不符合学院目录格式".to_string(),
+                },
+            });
+        }
+
+        let mut tasks = Vec::with_capacity(150);
+
+        for college_path in college_dirs {
+            let tx_clone = self.tx.clone();
+            let cancel = self.cancel.clone();
+
+            let task = tokio::task::spawn(async move {
+                let (college_data, college_report) = parse_college_dir(&college_path, &cancel);
+                if let Some(college_data) = college_data {
+                    tx_clone
+                        .send(college_data)
+                        .await
+                        .map_err(|e| CustomError::UnknownError(e.to_string()))?;
+                }
+                Ok::<_, CustomError>(college_report)
+            });
+            tasks.push(task);
+        }
+
+        let abort_handles: Vec<_> = tasks.iter().map(|task| task.abort_handle()).collect();
+        let results = tokio::select! {
+            results = join_all(tasks) => results,
+            _ = self.cancel.cancelled() => {
+                abort_handles.iter().for_each(|handle| handle.abort());
+                return Err(CustomError::Cancelled);
+            }
+        };
+        if self.cancel.is_cancelled() {
+            return Err(CustomError::Cancelled);
+        }
+        // a college which could not be parsed fails the whole import
+        for result in results {
+            let college_report = result.map_err(|e| CustomError::UnknownError(e.to_string()))??;
+            log_directories(&college_report);
+            report.extend(college_report);
+        }
+        Ok(report)
+    }
+
+    /// 直接读取zip压缩包中的csv文件, 无需事先解压
+    async fn produce_from_zip(&self, path: PathBuf) -> Result<ImportReport, CustomError> {
+        let cancel = self.cancel.clone();
+        let (colleges, report) =
+            tokio::task::spawn_blocking(move || read_zip_archive(&path, &cancel))
+                .await
+                .map_err(|e| CustomError::UnknownError(e.to_string()))??;
+        self.progress.dirs_discovered(colleges.len());
+        log_directories(&report);
+
+        for college_data in colleges {
+            self.tx
+                .send(college_data)
+                .await
+                .map_err(|e| CustomError::UnknownError(e.to_string()))?;
+        }
+        Ok(report)
+    }
+}
+
+/// 记录学院目录的解析结果
+fn log_directories(report: &ImportReport) {
+    for dir in &report.directories {
+        match &dir.outcome {
+            ImportOutcome::Failed { error } => log::error!("{}: {}", dir.path, error),
+            outcome => info!("{}: {:?}", dir.path, outcome),
+        }
+    }
+}
+
+/// 判断路径是否为zip压缩包
+fn is_zip_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// 解析一个学院目录下的所有csv文件
+///
+/// 学期目录名称不符合规范时, 不会返回学院数据;
+/// 单个csv文件解析失败时, 仅在报告中记录该文件, 其余文件照常导入;
+/// 令牌被取消时不再解析剩余的文件, 也不返回学院数据
+fn parse_college_dir(
+    college_path: &PathBuf,
+    cancel: &CancellationToken,
+) -> (Option<CollegeData>, ImportReport) {
+    let mut report = ImportReport::default();
+    let mut skipped_files = Vec::new();
+    let result = parse_term_and_college_info(college_path).and_then(|info| {
+        let csv_files = collect_csv_files(college_path, &mut skipped_files)?;
+        Ok((info, csv_files))
+    });
+
+    let ((term_name, college_name, college_number), csv_files) = match result {
+        Ok(val) => val,
+        Err(e) => {
+            report.directories.push(DirectoryReport {
+                path: college_path.to_string_lossy().to_string(),
+                term_name: None,
+                college_name: None,
+                outcome: ImportOutcome::Failed {
+                    error: e.to_string(),
+                },
+            });
+            return (None, report);
+        }
+    };
+
+    let mut college_data = CollegeData {
+        term_name,
+        college_name,
+        college_number,
+        data: Vec::new(),
+    };
+    report
+        .directories
+        .push(imported_dir_report(college_path, &college_data));
+    for file in skipped_files {
+        report.files.push(skipped_file_report(&file, &college_data));
+    }
+    for csv_file in csv_files {
+        if cancel.is_cancelled() {
+            return (None, report);
+        }
+        let result = csv_processor::CsvTableBuilder::new(&csv_file).build();
+        push_csv_table(&mut report, &mut college_data, &csv_file, result);
+    }
+
+    (Some(college_data), report)
+}
+
+fn imported_dir_report(college_path: &Path, college_data: &CollegeData) -> DirectoryReport {
+    DirectoryReport {
+        path: college_path.to_string_lossy().to_string(),
+        term_name: Some(college_data.term_name.to_string()),
+        college_name: Some(college_data.college_name.to_string()),
+        outcome: ImportOutcome::Imported,
+    }
+}
+
+fn skipped_file_report(file: &Path, college_data: &CollegeData) -> FileReport {
+    FileReport {
+        path: file.to_string_lossy().to_string(),
+        term_name: Some(college_data.term_name.to_string()),
+        college_name: Some(college_data.college_name.to_string()),
+        class_name: None,
+        rows: 0,
+        outcome: ImportOutcome::Skipped {
+            reason: "文件名称不符合班级成绩文件格式".to_string(),
+        },
+    }
+}
+
+/// 记录csv文件的解析结果, 解析成功的表加入学院数据
+fn push_csv_table(
+    report: &mut ImportReport,
+    college_data: &mut CollegeData,
+    csv_file: &Path,
+    result: Result<CsvTable, CustomError>,
+) {
+    let mut file_report = FileReport {
+        path: csv_file.to_string_lossy().to_string(),
+        term_name: Some(college_data.term_name.to_string()),
+        college_name: Some(college_data.college_name.to_string()),
+        class_name: None,
+        rows: 0,
+        outcome: ImportOutcome::Imported,
+    };
+    match result {
+        Ok(csv_table) => {
+            file_report.class_name = Some(csv_table.class_name.clone());
+            file_report.rows = csv_table.records.len();
+            college_data.data.push(csv_table);
+        }
+        Err(e) => {
+            log::error!("{}: {:?}", file_report.path, e);
+            file_report.outcome = ImportOutcome::Failed {
+                error: e.to_string(),
+            };
+        }
+    }
+    report.files.push(file_report);
+}
+
+/// 读取zip压缩包中`学期智育学分绩/NNcollege/*.csv`形式的条目, 按学院分组
+///
+/// 学期、学院、专业与班级信息的解析规则与目录导入相同
+///
+/// # Errors
+///
+/// 如果压缩包无法打开或已损坏，返回`CustomError::FileReadError`或`CustomError::ZipError`
+/// 如果令牌被取消，返回`CustomError::Cancelled`
+fn read_zip_archive(
+    path: &Path,
+    cancel: &CancellationToken,
+) -> Result<(Vec<CollegeData>, ImportReport), CustomError> {
+    let college_re = Regex::new(COLLEGE_DIR_PATTERN)?;
+    let csv_re = Regex::new(CSV_FILE_PATTERN)?;
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+
+    let mut report = ImportReport::default();
+    let mut colleges: Vec<CollegeData> = Vec::new();
+    // 学院目录在`colleges`中的位置, 学期目录不符合规范的学院为None
+    let mut college_index: HashMap<PathBuf, Option<usize>> = HashMap::new();
+
+    for i in 0..archive.len() {
+        if cancel.is_cancelled() {
+            return Err(CustomError::Cancelled);
+        }
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+
+        let entry_path = PathBuf::from(decode_entry_name(entry.name_raw()));
+        let college_path = match entry_path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => continue,
+        };
+        let in_college = get_file_name(&college_path).is_ok_and(|name| college_re.is_match(name));
+        if !in_college {
+            continue;
+        }
+
+        let index = match college_index.get(&college_path) {
+            Some(index) => *index,
+            None => {
+                let index = match parse_term_and_college_info(&college_path) {
+                    Ok((term_name, college_name, college_number)) => {
+                        let college_data = CollegeData {
+                            term_name,
+                            college_name,
+                            college_number,
+                            data: Vec::new(),
+                        };
+                        report
+                            .directories
+                            .push(imported_dir_report(&college_path, &college_data));
+                        colleges.push(college_data);
+                        Some(colleges.len() - 1)
+                    }
+                    Err(e) => {
+                        report.directories.push(DirectoryReport {
+                            path: college_path.to_string_lossy().to_string(),
+                            term_name: None,
+                            college_name: None,
+                            outcome: ImportOutcome::Failed {
+                                error: e.to_string(),
+                            },
+                        });
+                        None
+                    }
+                };
+                college_index.insert(college_path.clone(), index);
+                index
+            }
+        };
+        let college_data = match index {
+            Some(index) => &mut colleges[index],
+            None => continue,
+        };
+
+        let is_csv = get_file_name(&entry_path).is_ok_and(|name| csv_re.is_match(name));
+        if !is_csv {
+            report
+                .files
+                .push(skipped_file_report(&entry_path, college_data));
+            continue;
+        }
+        let result = csv_processor::CsvTableBuilder::new(&entry_path).build_from_reader(&mut entry);
+        push_csv_table(&mut report, college_data, &entry_path, result);
+    }
+
+    Ok((colleges, report))
+}
+
+/// 解码压缩包中的条目名称
+/// 在中文Windows下创建的压缩包通常使用GBK编码文件名, 非UTF-8时按GBK解码
+fn decode_entry_name(raw: &[u8]) -> String {
+    match std::str::from_utf8(raw) {
+        Ok(name) => name.to_string(),
+        Err(_) => encoding_rs::GBK.decode(raw).0.into_owned(),
+    }
+}
+
+/// 收集学院目录, 名称不符合规范且不含子目录的目录记录在`skipped`中
+fn collect_college_dirs(
+    path: &PathBuf,
+    re: &Regex,
+    buf: &mut Vec<PathBuf>,
+    skipped: &mut Vec<PathBuf>,
+) -> Result<(), CustomError> {
+    // get the files under the path
+    let dirs = fs::read_dir(path)?;
+
+    for entry in dirs {
+        if let Ok(file) = entry {
+            if file.path().is_dir() {
+                if re.is_match(file.file_name().to_str().expect("Invalid file name")) {
+                    buf.push(file.path());
+                } else if has_sub_dirs(&file.path())? {
+                    collect_college_dirs(&file.path(), re, buf, skipped)?;
+                } else {
+                    skipped.push(file.path());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn has_sub_dirs(path: &PathBuf) -> Result<bool, CustomError> {
+    Ok(fs::read_dir(path)?
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.path().is_dir()))
+}
+
+/// 收集学院目录下的csv文件, 名称不符合规范的文件记录在`skipped`中
+fn collect_csv_files(
+    dir_path: &PathBuf,
+    skipped: &mut Vec<PathBuf>,
+) -> Result<Vec<PathBuf>, CustomError> {
+    let mut csv_files = Vec::new();
+    let re = Regex::new(CSV_FILE_PATTERN)?;
+    let files = fs::read_dir(dir_path)?;
+
+    for entry in files {
+        if let Ok(file) = entry {
+            if file.path().is_file() {
+                match file.file_name().to_str() {
+                    Some(file_name) if re.is_match(file_name) => csv_files.push(file.path()),
+                    _ => skipped.push(file.path()),
+                }
+            }
+        }
+    }
+
+    Ok(csv_files)
+}
+
+fn parse_term_and_college_info(
+    college_path: &PathBuf,
+) -> Result<(Arc<String>, Arc<String>, Arc<String>), CustomError> {
+    let (college_name, college_number) = {
+        let t = get_file_name(college_path)?;
+        (t.chars().skip(2).collect(), t.chars().take(2).collect())
+    };
+    let term_path = college_path
+        .parent()
+        .ok_or(CustomError::UnexpectedFileError(
+            college_path.to_string_lossy().to_string(),
+        ))?
+        .to_path_buf();
+    verify_file_name(&term_path, TERM_DIR_PATTERN)?;
+    let term: String = {
+        let term_str = get_file_name(&term_path)?;
+        term_str.chars().take(11).collect()
+    };
+    // the academic year must be two consecutive years
+    Term::parse(&term)?;
+
+    Ok((
+        Arc::new(term),
+        Arc::new(college_name),
+        Arc::new(college_number),
+    ))
+}
+
+/// 校验文件名称
+fn verify_file_name(file_path: &PathBuf, re_str: &str) -> Result<(), CustomError> {
+    let re = Regex::new(re_str)?;
+
+    let file_name = get_file_name(file_path)?;
+    if !re.is_match(file_name) {
+        return Err(CustomError::UnexpectedFileError(format!(
+            "{} 文件名不符合要求",
+            file_name
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{error::Error, io::Write};
+    use tempfile::tempdir;
+    use tokio::sync::mpsc;
+
+    fn data_dir() -> Result<PathBuf, Box<dyn Error>> {
+        let mut current_dir = std::env::current_dir()?;
+        current_dir.push("test\\data");
+        Ok(current_dir)
+    }
+
+    #[tokio::test]
+    async fn test_data_producer_consumer() -> Result<(), Box<dyn Error>> {
+        let start = std::time::Instant::now();
+
+        let (tx, rx) = mpsc::channel(32);
+        let producer = DataProducer::new(tx);
+        let mut consumer = DataConsumer::new(rx);
+
+        let temp_path = data_dir()?;
+
+        let producer_task = tokio::spawn(async move {
+            producer.produce(temp_path).await.unwrap();
+        });
+
+        let consumer_task = tokio::spawn(async move { consumer.consume().await });
+
+        let (producer_result, consumer_result) = tokio::join!(producer_task, consumer_task);
+
+        let end = std::time::Instant::now();
+
+        if let Err(e) = producer_result {
+            eprintln!("Producer task failed: {:?}", e);
+        }
+
+        if let Err(e) = consumer_result {
+            eprintln!("Consumer task failed: {:?}", e);
+        } else if let Ok(records) = consumer_result {
+            assert_eq!(records.len(), 3571);
+            println!("Records count: {}", records.len());
+        }
+
+        println!("Time elapsed: {:?}", end - start);
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_college_dirs() {
+        let temp_dir = tempdir().unwrap();
+        let temp_path = temp_dir.path().to_path_buf();
+
+        // Create dummy directories
+        let college_dir = temp_path.join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+        let other_dir = temp_path.join("1College");
+        fs::create_dir_all(&other_dir).unwrap();
+
+        let re = Regex::new(r"^\d{2}.{2,10}$").unwrap();
+        let mut college_dirs = Vec::new();
+        let mut skipped_dirs = Vec::new();
+        collect_college_dirs(&temp_path, &re, &mut college_dirs, &mut skipped_dirs).unwrap();
+
+        assert_eq!(college_dirs.len(), 1);
+        assert_eq!(college_dirs[0], college_dir);
+        assert_eq!(skipped_dirs, vec![other_dir]);
+    }
+
+    #[test]
+    fn test_collect_csv_files() {
+        let temp_dir = tempdir().unwrap();
+        let temp_path = temp_dir.path().to_path_buf();
+
+        // Create dummy CSV files
+        let csv_file = temp_path.join("a21test2021hz.csv");
+        fs::write(&csv_file, "dummy content").unwrap();
+
+        let other_file = temp_path.join("readme.txt");
+        fs::write(&other_file, "dummy content").unwrap();
+
+        let mut skipped_files = Vec::new();
+        let csv_files = collect_csv_files(&temp_path, &mut skipped_files).unwrap();
+
+        assert_eq!(csv_files.len(), 1);
+        assert_eq!(csv_files[0], csv_file);
+        assert_eq!(skipped_files, vec![other_file]);
+    }
+
+    #[test]
+    fn test_parse_term_and_college_info() {
+        let temp_dir = tempdir().unwrap();
+        let temp_path = temp_dir.path().to_path_buf();
+
+        // Create dummy directories
+        let term_dir = temp_path.join("2021-2022-1学期智育学分绩");
+        let college_dir = term_dir.join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+
+        let (term, college_name, college_number) =
+            parse_term_and_college_info(&college_dir).unwrap();
+
+        assert_eq!(*term, "2021-2022-1");
+        assert_eq!(*college_name, "College");
+        assert_eq!(*college_number, "21");
+    }
+
+    #[test]
+    fn test_read_zip_archive() {
+        let temp_dir = tempdir().unwrap();
+        let zip_path = temp_dir.path().join("data_2021_2022.zip");
+
+        // Create a dummy archive with the published layout
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer
+            .add_directory(
+                "data_2021_2022/2021-2022-1学期智育学分绩/21College/",
+                options,
+            )
+            .unwrap();
+        writer
+            .start_file(
+                "data_2021_2022/2021-2022-1学期智育学分绩/21College/a21major2101hz.csv",
+                options,
+            )
+            .unwrap();
+        writer
+            .write_all(
+                "xh,xm,k101\n,,00231|0.0|2021-2022-1智育学分绩||\n12345,John Doe,3.5\n".as_bytes(),
+            )
+            .unwrap();
+        writer
+            .start_file("data_2021_2022/readme.txt", options)
+            .unwrap();
+        writer
+            .start_file(
+                "data_2021_2022/2021-2022-1学期智育学分绩/21College/a21major2102hz.csv",
+                options,
+            )
+            .unwrap();
+        writer
+            .start_file(
+                "data_2021_2022/2021-2022-1学期智育学分绩/21College/notes.txt",
+                options,
+            )
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert!(is_zip_file(&zip_path));
+        let (colleges, report) = read_zip_archive(&zip_path, &CancellationToken::new()).unwrap();
+
+        assert_eq!(colleges.len(), 1);
+        assert_eq!(*colleges[0].term_name, "2021-2022-1");
+        assert_eq!(*colleges[0].college_name, "College");
+        assert_eq!(*colleges[0].college_number, "21");
+        assert_eq!(colleges[0].data.len(), 1);
+        assert_eq!(colleges[0].data[0].class_name, "major2101");
+        assert_eq!(colleges[0].data[0].records.len(), 1);
+
+        assert_eq!(report.directories.len(), 1);
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(report.files[0].rows, 1);
+        assert!(matches!(
+            report.files[1].outcome,
+            ImportOutcome::Failed { .. }
+        ));
+        assert!(matches!(
+            report.files[2].outcome,
+            ImportOutcome::Skipped { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_college_dir() {
+        let temp_dir = tempdir().unwrap();
+        let college_dir = temp_dir
+            .path()
+            .join("2021-2022-1学期智育学分绩")
+            .join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+        fs::write(
+            college_dir.join("a21major2101hz.csv"),
+            "xh,xm,k101\n,,00231|0.0|2021-2022-1智育学分绩||\n12345,John Doe,3.5\n",
+        )
+        .unwrap();
+        fs::write(college_dir.join("a21major2102hz.csv"), "").unwrap();
+
+        let (college_data, report) = parse_college_dir(&college_dir, &CancellationToken::new());
+
+        let college_data = college_data.unwrap();
+        assert_eq!(college_data.data.len(), 1);
+        assert_eq!(report.directories[0].outcome, ImportOutcome::Imported);
+        let failed = report
+            .files
+            .iter()
+            .filter(|file| matches!(file.outcome, ImportOutcome::Failed { .. }))
+            .count();
+        assert_eq!(failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_produce_cancelled() {
+        let temp_dir = tempdir().unwrap();
+        let college_dir = temp_dir
+            .path()
+            .join("2021-2022-1学期智育学分绩")
+            .join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(32);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let producer = DataProducer::new(tx).with_cancellation(cancel);
+
+        let result = producer.produce(temp_dir.path().to_path_buf()).await;
+
+        assert!(matches!(result, Err(CustomError::Cancelled)));
+        drop(producer);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_parse_college_dir_invalid_term() {
+        let temp_dir = tempdir().unwrap();
+        let college_dir = temp_dir.path().join("2021-2022-1").join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+
+        let (college_data, report) = parse_college_dir(&college_dir, &CancellationToken::new());
+
+        assert!(college_data.is_none());
+        assert!(matches!(
+            report.directories[0].outcome,
+            ImportOutcome::Failed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_decode_entry_name() {
+        let (gbk, _, _) = encoding_rs::GBK.encode("2021-2022-1学期智育学分绩");
+        assert_eq!(decode_entry_name(&gbk), "2021-2022-1学期智育学分绩");
+        assert_eq!(decode_entry_name("21College".as_bytes()), "21College");
+    }
+
+    #[test]
+    fn test_verify_file_name() {
+        let temp_dir = tempdir().unwrap();
+        let temp_path = temp_dir.path().to_path_buf();
+
+        // Create dummy directory
+        let term_dir = temp_path.join("2021-2022-1学期智育学分绩");
+        fs::create_dir_all(&term_dir).unwrap();
+
+        let result = verify_file_name(&term_dir, TERM_DIR_PATTERN);
+
+        assert!(result.is_ok());
+
+        // the summer term
+        let term_dir = temp_path.join("2021-2022-3学期智育学分绩");
+        fs::create_dir_all(&term_dir).unwrap();
+        assert!(verify_file_name(&term_dir, TERM_DIR_PATTERN).is_ok());
+
+        let term_dir = temp_path.join("2021-2022-4学期智育学分绩");
+        fs::create_dir_all(&term_dir).unwrap();
+        assert!(verify_file_name(&term_dir, TERM_DIR_PATTERN).is_err());
+    }
+}
@@ -0,0 +1,2877 @@
+pub mod table;
+
+use super::csv_processor::{CsvRecords, CsvTable};
+use crate::data_parser::CollegeData;
+use crate::err::CustomError;
+use crate::progress::ProgressReporter;
+use crate::ranking::rank_rows;
+use crate::report::InsertResult;
+use crate::term::Term;
+use log::info;
+use serde::Deserialize;
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteConnectOptions};
+use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::path::Path;
+use table::{
+    ClassChange, ClassInfo, CollegeInfo, MajorInfo, MisplacedClass, ResultRow, StudentHistory,
+    StudentInfo, TermInfo, TermRecord, Transfer,
+};
+use tokio_util::sync::CancellationToken;
+
+/// how to handle the terms that are already loaded into the database
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeMode {
+    /// delete the loaded records of the term and import it again
+    #[default]
+    Replace,
+    /// keep the loaded records and only import the new terms
+    Skip,
+}
+
+/// how to aggregate the gpa of several terms
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum Aggregation {
+    /// the sum of the terms with a gpa, only fair when every student has all the terms
+    #[default]
+    Sum,
+    /// the arithmetic mean of the terms with a gpa
+    Mean,
+    /// the mean of the terms with a gpa, weighted by the weight of each term id
+    WeightedMean { weights: HashMap<i64, f64> },
+}
+
+impl Aggregation {
+    /// whether the aggregated gpa of the students missing some terms is comparable to
+    /// the others, so that they can be ranked together
+    pub fn compares_incomplete(&self) -> bool {
+        !matches!(self, Aggregation::Sum)
+    }
+
+    /// the sql expression aggregating `academic_records.gpa` of the terms
+    ///
+    /// the term ids are bound as the parameters starting from `?{first_param}`, and the weighted
+    /// mean takes the weights bound right after them, see [`weights`](Self::weights)
+    fn sql_expression(&self, term_count: usize, first_param: usize) -> String {
+        match self {
+            Aggregation::Sum => "SUM( academic_records.gpa )".to_string(),
+            Aggregation::Mean => "AVG( academic_records.gpa )".to_string(),
+            Aggregation::WeightedMean { .. } => {
+                let cases: String = (first_param..first_param + term_count)
+                    .map(|param| format!(" WHEN ?{} THEN ?{}", param, param + term_count))
+                    .collect();
+                let weight = format!("( CASE academic_records.term_id{} END )", cases);
+                // the terms without a gpa are not counted in the total weight
+                format!(
+                    "SUM( academic_records.gpa * {w} ) / SUM( CASE WHEN academic_records.gpa IS NOT NULL THEN {w} END )",
+                    w = weight
+                )
+            }
+        }
+    }
+
+    /// the weights of the terms in order, bound after the term ids; empty unless weighted
+    fn weights(&self, terms: &[i64]) -> Result<Vec<f64>, CustomError> {
+        let Aggregation::WeightedMean { weights } = self else {
+            return Ok(vec![]);
+        };
+        terms
+            .iter()
+            .map(|term_id| {
+                let weight = *weights
+                    .get(term_id)
+                    .ok_or(CustomError::ValidationError(format!(
+                        "缺少学期{}的权重",
+                        term_id
+                    )))?;
+                if !weight.is_finite() || weight < 0.0 {
+                    return Err(CustomError::ValidationError(format!(
+                        "学期{}的权重不合法: {}",
+                        term_id, weight
+                    )));
+                }
+                Ok(weight)
+            })
+            .collect()
+    }
+}
+
+/// which major, or class, places the students in the [`RankScope`]
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Cohort {
+    /// the class of the chronologically latest selected term, the students not in the scope in
+    /// that term are left out
+    #[default]
+    LastSelectedTerm,
+    /// the class of the latest loaded term of each student, the students transferred out of
+    /// the scope are listed but not ranked
+    LatestMajor,
+    /// the class of the earliest loaded term of each student, the students transferred into
+    /// the scope are listed but not ranked
+    OriginalMajor,
+}
+
+/// the students ranked together, by the class they are in
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "kind"
+)]
+pub enum RankScope {
+    /// the students of the class
+    Class { class_id: i64 },
+    /// the students of the major
+    Major { major_id: i64 },
+    /// the students of any of the majors, such as the majors reviewed by the same committee
+    Majors { major_ids: Vec<i64> },
+    /// the students of every major of the college
+    College { college_id: i64 },
+    /// every student of the grade in the university
+    Grade,
+}
+
+impl RankScope {
+    /// the sql condition telling whether the class, given by its class id and major id
+    /// columns, is in the scope
+    ///
+    /// the ids of the scope are bound as the parameters starting from `?{first_param}`,
+    /// see [`ids`](Self::ids)
+    fn sql_condition(&self, class_column: &str, major_column: &str, first_param: usize) -> String {
+        match self {
+            RankScope::Class { .. } => format!("{} = ?{}", class_column, first_param),
+            RankScope::Major { .. } => format!("{} = ?{}", major_column, first_param),
+            RankScope::Majors { major_ids } => format!(
+                "{} IN ( {} )",
+                major_column,
+                numbered_placeholders(first_param, major_ids.len())
+            ),
+            RankScope::College { .. } => format!(
+                "{} IN ( SELECT major_id FROM majors WHERE college_id = ?{} )",
+                major_column, first_param
+            ),
+            RankScope::Grade => "1".to_string(),
+        }
+    }
+
+    /// the ids bound to the condition of the scope in order
+    fn ids(&self) -> Vec<i64> {
+        match self {
+            RankScope::Class { class_id } => vec![*class_id],
+            RankScope::Major { major_id } => vec![*major_id],
+            RankScope::Majors { major_ids } => major_ids.clone(),
+            RankScope::College { college_id } => vec![*college_id],
+            RankScope::Grade => vec![],
+        }
+    }
+}
+
+/// a preset selecting the terms instead of picking them one by one, only the fall and spring
+/// terms are selected, the summer terms have to be given explicitly
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum TermPreset {
+    /// the academic year starting in the year, such as 2023 for `2023-2024`
+    AcademicYear { year: i32 },
+    /// every term since the grade enrolled
+    SinceEnrollment,
+    /// the latest loaded terms
+    LastTerms { count: u32 },
+}
+
+impl TermPreset {
+    /// the sql selecting the term ids of the preset, with its parameter bound as `?{param}`
+    fn sql_expression(&self, param: usize) -> String {
+        let condition = match self {
+            TermPreset::AcademicYear { .. } => format!("AND start_year = ?{}", param),
+            TermPreset::SinceEnrollment => format!("AND start_year >= ?{}", param),
+            TermPreset::LastTerms { .. } => String::new(),
+        };
+        let limit = match self {
+            TermPreset::LastTerms { .. } => format!("LIMIT ?{}", param),
+            _ => String::new(),
+        };
+        format!(
+            "SELECT term_id FROM terms WHERE semester IN ( 1, 2 ) {}
+            ORDER BY start_year DESC, semester DESC {}",
+            condition, limit
+        )
+    }
+}
+
+/// the result row with the original and the latest major of the student
+#[derive(sqlx::FromRow)]
+struct CohortRow {
+    #[sqlx(flatten)]
+    row: ResultRow,
+    original_major_id: i64,
+    original_major: String,
+    latest_major_id: i64,
+    latest_major: String,
+    original_in_scope: bool,
+    latest_in_scope: bool,
+}
+
+/// the max count of the students returned by a search
+const MAX_SEARCH_RESULTS: i64 = 50;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Pool<Sqlite>,
+}
+
+impl AppState {
+    /// open the database file, create it and run the migrations if needed
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the path of the database file
+    ///
+    /// # Errors
+    ///
+    /// return the error if the database can't be created or migrated
+    pub async fn open(path: &Path) -> Result<Self, CustomError> {
+        // test if the database file exists
+        if !path.exists() {
+            // create the database file if it doesn't exist
+            std::fs::File::create(path)?;
+        }
+
+        // connect the database, the path is passed as is instead of spliced into a url
+        let pool = SqlitePool::connect_with(SqliteConnectOptions::new().filename(path)).await?;
+        // use the migration feature of sqlx to create the table
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        let app_state = AppState { db: pool };
+        // the classes merged by the old versions that the migration can't place
+        for class in app_state.get_misplaced_classes().await? {
+            log::warn!(
+                "{} {} {}: {}/{}名学生在其他学期属于{}, 可能是从该学院合并而来, 请以覆盖模式重新导入对应学期",
+                class.college_name,
+                class.major_name,
+                class.class_name,
+                class.other_students,
+                class.students,
+                class.other_college_name
+            );
+        }
+        Ok(app_state)
+    }
+
+    /// get the classes most of whose students are recorded under one other college in the
+    /// other terms
+    ///
+    /// the old versions merged the majors and the classes of the same name in different
+    /// colleges; the migration moves the classes whose students all belong to one other
+    /// college out of the majors mixing several colleges, and the rest are listed here for the
+    /// user to re-import the terms in [`MergeMode::Replace`]. a cross-college transfer of most
+    /// of a class is listed too
+    pub async fn get_misplaced_classes(&self) -> Result<Vec<MisplacedClass>, CustomError> {
+        let classes = sqlx::query_as(
+            r"WITH class_students AS (
+                SELECT class_id, COUNT(DISTINCT student_id) AS students
+                FROM academic_records
+                GROUP BY class_id
+            ),
+            -- the colleges the students of each class are recorded under in the other classes
+            outside AS (
+                SELECT DISTINCT own.class_id, own.student_id, other_majors.college_id
+                FROM academic_records AS own
+                JOIN academic_records AS other ON other.student_id = own.student_id
+                    AND other.class_id <> own.class_id
+                JOIN classes AS other_classes ON other_classes.class_id = other.class_id
+                JOIN majors AS other_majors ON other_majors.major_id = other_classes.major_id
+            ),
+            counted AS (
+                SELECT class_id, college_id, COUNT(*) AS other_students
+                FROM outside
+                GROUP BY class_id, college_id
+            )
+            SELECT
+                colleges.college_name,
+                majors.major_name,
+                classes.class_name,
+                other_colleges.college_name AS other_college_name,
+                class_students.students,
+                counted.other_students
+            FROM counted
+            JOIN classes ON classes.class_id = counted.class_id
+            JOIN majors ON majors.major_id = classes.major_id
+            JOIN colleges ON colleges.college_id = majors.college_id
+            JOIN colleges AS other_colleges ON other_colleges.college_id = counted.college_id
+            JOIN class_students ON class_students.class_id = counted.class_id
+            WHERE counted.college_id <> majors.college_id
+            AND counted.other_students * 2 >= class_students.students
+            ORDER BY colleges.college_id, majors.major_name, classes.class_name;",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(classes)
+    }
+
+    /// set the csv data
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the csv data
+    /// * `mode` - how to handle the terms that are already loaded
+    /// * `progress` - the reporter of the inserted rows
+    /// * `cancel` - the token to cancel the import, checked between the colleges and before
+    ///   the commit
+    ///
+    /// # Returns
+    ///
+    /// the skipped terms
+    ///
+    /// # Errors
+    ///
+    /// return the error if any record failed to insert, or `CustomError::Cancelled` if the
+    /// token is cancelled before the commit, in which case nothing is written
+    pub async fn set(
+        &self,
+        data: Vec<CollegeData>,
+        mode: MergeMode,
+        progress: &ProgressReporter,
+        cancel: &CancellationToken,
+    ) -> Result<InsertResult, CustomError> {
+        // sort the data by term name
+        let mut data = data;
+        data.sort_by(|a, b| a.term_name.cmp(&b.term_name));
+
+        // find the terms that are already loaded
+        let loaded_terms: HashSet<String> =
+            sqlx::query_scalar::<_, String>(r"SELECT term_name FROM terms;")
+                .fetch_all(&self.db)
+                .await?
+                .into_iter()
+                .collect();
+        let mut skipped_terms: Vec<String> = data
+            .iter()
+            .map(|college_data| college_data.term_name.to_string())
+            .filter(|term_name| loaded_terms.contains(term_name))
+            .collect();
+        skipped_terms.dedup();
+        if mode == MergeMode::Skip {
+            data.retain(|college_data| !loaded_terms.contains(college_data.term_name.as_str()));
+            info!("Skipping loaded terms: {:?}", skipped_terms);
+        }
+
+        info!("Inserting academic info");
+
+        // the whole import runs in one transaction: if any statement fails, or the import is
+        // cancelled, the transaction is rolled back and the previously loaded data stays
+        // queryable
+        let mut tx = self.db.begin().await?;
+        let replaced = mode == MergeMode::Replace && !skipped_terms.is_empty();
+        if mode == MergeMode::Replace {
+            for term_name in &skipped_terms {
+                info!("Replacing loaded term: {}", term_name);
+                delete_term_records(&mut tx, term_name).await?;
+            }
+        }
+
+        // insert the term and colleges info at first
+        let (terms_map, classes_map) = insert_academic_info(&mut tx, &data).await?;
+
+        let total_rows = data
+            .iter()
+            .flat_map(|college_data| &college_data.data)
+            .map(|table| table.records.len())
+            .sum();
+        progress.insert_started(total_rows);
+
+        let mut insert_result = InsertResult::default();
+        if mode == MergeMode::Skip {
+            insert_result.skipped_terms = skipped_terms;
+        }
+        for college_data in data {
+            if cancel.is_cancelled() {
+                return Err(CustomError::Cancelled);
+            }
+            // extract the college data
+            let CollegeData {
+                term_name,
+                college_name,
+                college_number,
+                data,
+            } = college_data;
+            let term_id = *terms_map
+                .get(term_name.as_str())
+                .ok_or(CustomError::UnknownError(format!(
+                    "未知的学期: {}",
+                    term_name
+                )))?;
+
+            let row_cnt =
+                insert_college_records(&mut tx, data, term_id, &college_number, &classes_map)
+                    .await
+                    .map_err(|e| {
+                        log::error!("Failed to insert {}-{}: {}", term_name, college_name, e);
+                        e
+                    })?;
+            progress.rows_inserted(&term_name, &college_name, row_cnt);
+        }
+        if replaced {
+            delete_unused_classes(&mut tx).await?;
+        }
+        // the last chance to cancel, once started the commit always runs to the end
+        if cancel.is_cancelled() {
+            return Err(CustomError::Cancelled);
+        }
+        tx.commit().await?;
+
+        Ok(insert_result)
+    }
+
+    /// get the loaded term info in chronological order
+    pub async fn get_terms(&self) -> Result<Vec<TermInfo>, CustomError> {
+        let terms: Vec<TermInfo> = sqlx::query_as(
+            r"SELECT term_id, term_name, start_year, end_year, semester FROM terms
+            ORDER BY start_year, semester, term_name;",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(terms)
+    }
+
+    /// get the all colleges
+    pub async fn get_colleges(&self) -> Result<Vec<CollegeInfo>, CustomError> {
+        let colleges: Vec<CollegeInfo> =
+            sqlx::query_as(r"SELECT college_id, college_name FROM colleges;")
+                .fetch_all(&self.db)
+                .await?;
+        Ok(colleges)
+    }
+
+    /// get the majors under the college
+    pub async fn get_majors(&self, college_id: i64) -> Result<Vec<MajorInfo>, CustomError> {
+        let majors: Vec<MajorInfo> =
+            sqlx::query_as(r"SELECT major_id, major_name FROM majors WHERE college_id = ?1;")
+                .bind(college_id)
+                .fetch_all(&self.db)
+                .await?;
+        Ok(majors)
+    }
+
+    /// get the grades, i.e. the enrollment years, of the classes under the major, or of all
+    /// the classes if the major is not given, latest first
+    pub async fn get_grades(&self, major_id: Option<i64>) -> Result<Vec<i32>, CustomError> {
+        let grades: Vec<i32> = sqlx::query_scalar(
+            r"SELECT DISTINCT enrollment_year FROM classes
+            WHERE ( ?1 IS NULL OR major_id = ?1 ) AND enrollment_year IS NOT NULL
+            ORDER BY enrollment_year DESC;",
+        )
+        .bind(major_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(grades)
+    }
+
+    /// get the classes under the major in the grade, such as 2021
+    pub async fn get_classes(
+        &self,
+        major_id: i64,
+        grade: i32,
+    ) -> Result<Vec<ClassInfo>, CustomError> {
+        let classes: Vec<ClassInfo> = sqlx::query_as(
+            r"SELECT class_id, class_name FROM classes
+            WHERE major_id = ?1 AND enrollment_year = ?2
+            ORDER BY class_number, class_name;",
+        )
+        .bind(major_id)
+        .bind(grade)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(classes)
+    }
+
+    /// get the students whose class changed between two consecutive terms of their records,
+    /// such as the major transfers, the splitting of a broad major and the class reshuffles
+    ///
+    /// # Arguments
+    ///
+    /// * `major_id` - only the changes into or out of the major, optional
+    /// * `grade` - only the changes into or out of the classes of the grade, such as `2021`, optional
+    ///
+    /// # Returns
+    ///
+    /// the changes ordered by the term and the student number
+    pub async fn get_class_changes(
+        &self,
+        major_id: Option<i64>,
+        grade: Option<i32>,
+    ) -> Result<Vec<ClassChange>, CustomError> {
+        let changes: Vec<ClassChange> = sqlx::query_as(
+            r"WITH placed AS (
+                SELECT
+                    academic_records.student_id,
+                    academic_records.term_id,
+                    terms.term_name,
+                    terms.start_year,
+                    terms.semester,
+                    classes.class_id,
+                    classes.class_name,
+                    classes.enrollment_year,
+                    majors.major_id,
+                    majors.major_name,
+                    LAG( terms.term_name ) OVER w AS from_term_name,
+                    LAG( classes.class_id ) OVER w AS from_class_id,
+                    LAG( classes.class_name ) OVER w AS from_class_name,
+                    LAG( classes.enrollment_year ) OVER w AS from_enrollment_year,
+                    LAG( majors.major_id ) OVER w AS from_major_id,
+                    LAG( majors.major_name ) OVER w AS from_major_name
+                FROM academic_records
+                JOIN terms ON terms.term_id = academic_records.term_id
+                JOIN classes ON classes.class_id = academic_records.class_id
+                JOIN majors ON majors.major_id = classes.major_id
+                WINDOW w AS (
+                    PARTITION BY academic_records.student_id ORDER BY terms.start_year, terms.semester
+                )
+            )
+            SELECT
+                students.student_number,
+                students.name,
+                placed.from_term_name,
+                placed.term_id,
+                placed.term_name,
+                placed.from_class_name AS from_class,
+                placed.class_name AS to_class,
+                placed.from_major_name AS from_major,
+                placed.major_name AS to_major,
+                ( placed.from_major_id <> placed.major_id ) AS major_changed
+            FROM placed
+            JOIN students ON students.student_id = placed.student_id
+            WHERE placed.from_class_id IS NOT NULL
+            AND placed.from_class_id <> placed.class_id
+            AND ( ?1 IS NULL OR ?1 IN ( placed.from_major_id, placed.major_id ) )
+            AND ( ?2 IS NULL OR ?2 IN ( placed.from_enrollment_year, placed.enrollment_year ) )
+            ORDER BY placed.start_year, placed.semester, students.student_number;",
+        )
+        .bind(major_id)
+        .bind(grade)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(changes)
+    }
+
+    /// search the students by the student number or the name
+    ///
+    /// the student number matches by prefix, and the name matches fuzzily: the characters of
+    /// the keyword must appear in the name in order, e.g. `张三` matches `张小三`
+    ///
+    /// # Arguments
+    ///
+    /// * `keyword` - the student number or the name
+    ///
+    /// # Returns
+    ///
+    /// at most `MAX_SEARCH_RESULTS` students, the exact matches of the student number first
+    pub async fn search_students(&self, keyword: &str) -> Result<Vec<StudentInfo>, CustomError> {
+        let keyword = keyword.trim();
+        if keyword.is_empty() {
+            return Ok(vec![]);
+        }
+        let escaped = escape_like(keyword);
+        let name_pattern = format!(
+            "%{}%",
+            escape_like_chars(keyword)
+                .collect::<Vec<String>>()
+                .join("%")
+        );
+
+        let students: Vec<StudentInfo> = sqlx::query_as(
+            r"SELECT student_id, student_number, name FROM students
+            WHERE student_number LIKE ?1 || '%' ESCAPE '\' OR name LIKE ?2 ESCAPE '\'
+            ORDER BY student_number <> ?3, student_number
+            LIMIT ?4;",
+        )
+        .bind(escaped)
+        .bind(name_pattern)
+        .bind(keyword)
+        .bind(MAX_SEARCH_RESULTS)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(students)
+    }
+
+    /// get the class, major, college, gpa and rank of each term of the student
+    ///
+    /// the rank within the major is among the students of the same grade, like [`get_gpa`](Self::get_gpa)
+    pub async fn get_student_terms(&self, student_id: i64) -> Result<Vec<TermRecord>, CustomError> {
+        let terms: Vec<TermRecord> = sqlx::query_as(
+            r"WITH ranked AS (
+                SELECT
+                    academic_records.student_id,
+                    academic_records.term_id,
+                    academic_records.class_id,
+                    academic_records.gpa,
+                    CASE WHEN academic_records.gpa IS NULL THEN NULL ELSE RANK() OVER (
+                        PARTITION BY academic_records.term_id, academic_records.class_id
+                        ORDER BY academic_records.gpa DESC
+                    ) END AS class_rank,
+                    COUNT(academic_records.gpa) OVER (
+                        PARTITION BY academic_records.term_id, academic_records.class_id
+                    ) AS class_size,
+                    CASE WHEN academic_records.gpa IS NULL THEN NULL ELSE RANK() OVER (
+                        PARTITION BY academic_records.term_id, classes.major_id, classes.enrollment_year
+                        ORDER BY academic_records.gpa DESC
+                    ) END AS major_rank,
+                    COUNT(academic_records.gpa) OVER (
+                        PARTITION BY academic_records.term_id, classes.major_id, classes.enrollment_year
+                    ) AS major_size
+                FROM academic_records
+                JOIN classes ON classes.class_id = academic_records.class_id
+                WHERE academic_records.term_id IN (
+                    SELECT term_id FROM academic_records WHERE student_id = ?1
+                )
+            )
+            SELECT
+                terms.term_id,
+                terms.term_name,
+                classes.class_name,
+                majors.major_name,
+                colleges.college_name,
+                ranked.gpa,
+                ranked.class_rank,
+                ranked.class_size,
+                ranked.major_rank,
+                ranked.major_size
+            FROM ranked
+            JOIN terms ON terms.term_id = ranked.term_id
+            JOIN classes ON classes.class_id = ranked.class_id
+            JOIN majors ON majors.major_id = classes.major_id
+            JOIN colleges ON colleges.college_id = majors.college_id
+            WHERE ranked.student_id = ?1
+            ORDER BY terms.start_year, terms.semester;",
+        )
+        .bind(student_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(terms)
+    }
+
+    /// search the students by the student number or the name, and get the history of each
+    ///
+    /// see [`search_students`](Self::search_students) for how the keyword matches
+    pub async fn get_student_history(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<StudentHistory>, CustomError> {
+        let mut histories = Vec::new();
+        for student in self.search_students(keyword).await? {
+            let terms = self.get_student_terms(student.student_id).await?;
+            histories.push(StudentHistory { student, terms });
+        }
+        Ok(histories)
+    }
+
+    /// resolve the selected terms to the term ids in chronological order
+    ///
+    /// # Arguments
+    ///
+    /// * `terms` - the term ids selected explicitly
+    /// * `preset` - the preset selecting more terms, optional
+    /// * `grade` - the grade, i.e. the enrollment year, required by [`TermPreset::SinceEnrollment`]
+    ///
+    /// # Errors
+    ///
+    /// return `CustomError::ValidationError` if the preset is missing its argument or no term
+    /// is selected at all
+    pub async fn resolve_terms(
+        &self,
+        terms: &[i64],
+        preset: Option<TermPreset>,
+        grade: Option<i32>,
+    ) -> Result<Vec<i64>, CustomError> {
+        let Some(preset) = preset else {
+            if terms.is_empty() {
+                return Err(CustomError::ValidationError("未选择学期".to_string()));
+            }
+            return Ok(terms.to_vec());
+        };
+        let argument = match preset {
+            TermPreset::AcademicYear { year } => year as i64,
+            TermPreset::SinceEnrollment => grade.ok_or(CustomError::ValidationError(
+                "按入学以来选择学期需要指定年级".to_string(),
+            ))? as i64,
+            TermPreset::LastTerms { count: 0 } => {
+                return Err(CustomError::ValidationError(
+                    "最近的学期数必须大于0".to_string(),
+                ))
+            }
+            TermPreset::LastTerms { count } => count as i64,
+        };
+
+        // the explicit term ids are bound from ?1, followed by the argument of the preset
+        let sql_str = format!(
+            r"SELECT term_id FROM terms
+            WHERE term_id IN ( {} ) OR term_id IN ( {} )
+            ORDER BY start_year, semester, term_name;",
+            numbered_placeholders(1, terms.len()),
+            preset.sql_expression(terms.len() + 1),
+        );
+        let mut query = sqlx::query_scalar(&sql_str);
+        for term_id in terms {
+            query = query.bind(term_id);
+        }
+        let term_ids: Vec<i64> = query.bind(argument).fetch_all(&self.db).await?;
+        if term_ids.is_empty() {
+            return Err(CustomError::ValidationError(
+                "没有符合条件的学期".to_string(),
+            ));
+        }
+        Ok(term_ids)
+    }
+
+    /// get the gpa by the given arguments
+    ///
+    /// # Arguments
+    ///
+    /// * `terms` - the terms id slice, in any order; the students are the ones in the scope
+    ///   at the chronologically latest of the terms
+    /// * `scope` - the students ranked together, such as a major or a whole college
+    /// * `grade` - the grade, i.e. the enrollment year, such as 2021
+    /// * `aggregation` - how to aggregate the gpa of several terms
+    /// * `cohort` - whether the students are placed by the latest selected term or by their
+    ///   latest or original class
+    ///
+    /// # Returns
+    ///
+    /// the rows sorted by gpa in descending order, with the rank of each student and the
+    /// major and the college of the class
+    ///
+    /// the missing gpa is NULL in the database and never counted as 0. For several terms
+    /// the gpa is aggregated over the terms with a gpa, `gpa_terms` tells how many terms
+    /// contributed, and the row is marked as `incomplete` if any of the selected terms is
+    /// missing. See [`rank_rows`] for how the ties and the incomplete rows are ranked
+    ///
+    /// # Errors
+    ///
+    /// return `CustomError::ValidationError` if the scope has no major or a weight is missing
+    pub async fn get_gpa(
+        &self,
+        terms: &[i64],
+        scope: &RankScope,
+        grade: i32,
+        aggregation: &Aggregation,
+        cohort: Cohort,
+    ) -> Result<Vec<ResultRow>, CustomError> {
+        if matches!(scope, RankScope::Majors { major_ids } if major_ids.is_empty()) {
+            return Err(CustomError::ValidationError("未选择专业".to_string()));
+        }
+        if cohort != Cohort::LastSelectedTerm {
+            return self
+                .get_gpa_by_major_history(terms, scope, grade, aggregation, cohort)
+                .await;
+        }
+        // the grade is bound as ?1, followed by the ids of the scope, the term ids and the
+        // weights if any
+        let scope_ids = scope.ids();
+        let first_term_param = 2 + scope_ids.len();
+        let (sql_str, weights) = match terms.len() {
+            1 => (
+                format!(
+                    r"SELECT    class_name AS class,
+                                major_name AS major,
+                                college_name AS college,
+                                student_number AS sno,
+                                students.name AS name,
+                                gpa,
+                                ( gpa IS NOT NULL ) AS gpa_terms
+                    FROM academic_records
+                    JOIN students ON academic_records.student_id = students.student_id
+                    JOIN classes ON academic_records.class_id = classes.class_id
+                    JOIN majors ON majors.major_id = classes.major_id
+                    JOIN colleges ON colleges.college_id = majors.college_id
+                    WHERE academic_records.term_id = ?{term}
+                    AND classes.enrollment_year = ?1
+                    AND {scope};",
+                    term = first_term_param,
+                    scope = scope.sql_condition("classes.class_id", "classes.major_id", 2),
+                ),
+                vec![],
+            ),
+            _ => (
+                format!(
+                    r"SELECT s.cname AS class, s.mname AS major, s.coname AS college, s.sno AS sno, s.sname AS name, {aggregation} AS gpa, COUNT( academic_records.gpa ) AS gpa_terms
+                    FROM academic_records
+                    JOIN (
+                        SELECT
+                        -- 学生基础信息字段
+                        students.student_id AS sid,
+                        students.student_number AS sno,
+                        students.name AS sname,
+                        -- 班级相关字段
+                        classes.class_name AS cname,
+                        majors.major_name AS mname,
+                        colleges.college_name AS coname
+                        FROM
+                          students
+                          JOIN academic_records ON academic_records.student_id = students.student_id
+                          -- 所选学期中按时间最晚的学期所在的班级与年级
+                          AND academic_records.term_id = (
+                            SELECT term_id FROM terms WHERE term_id IN ( {placeholders} )
+                            ORDER BY start_year DESC, semester DESC, term_name DESC LIMIT 1
+                          )
+                          JOIN classes ON classes.class_id = academic_records.class_id
+                          AND classes.enrollment_year = ?1
+                          AND {scope}
+                          JOIN majors ON majors.major_id = classes.major_id
+                          JOIN colleges ON colleges.college_id = majors.college_id
+                        GROUP BY
+                          students.student_id
+                        ) AS s
+                        ON academic_records.student_id = s.sid
+                            AND academic_records.term_id IN ( {placeholders} )
+                    GROUP BY s.sid;",
+                    aggregation = aggregation.sql_expression(terms.len(), first_term_param),
+                    placeholders = numbered_placeholders(first_term_param, terms.len()),
+                    scope = scope.sql_condition("classes.class_id", "classes.major_id", 2),
+                ),
+                aggregation.weights(terms)?,
+            ),
+        };
+        let mut query = sqlx::query_as(&sql_str).bind(grade);
+        for id in scope_ids {
+            query = query.bind(id);
+        }
+        for term_id in terms {
+            query = query.bind(term_id);
+        }
+        for weight in weights {
+            query = query.bind(weight);
+        }
+        let mut result: Vec<ResultRow> = query.fetch_all(&self.db).await?;
+        // the gpa of the terms without a record or with a missing gpa is not counted in the sum
+        for row in &mut result {
+            row.incomplete = row.gpa_terms < terms.len() as i64;
+        }
+        rank_rows(&mut result, aggregation.compares_incomplete());
+        Ok(result)
+    }
+
+    /// get the gpa of the students ranked by the class they are in at the latest or the
+    /// earliest loaded term, see [`Cohort`]
+    ///
+    /// the students who are in the scope at either of the two terms are listed, and the ones
+    /// who changed their major into or out of the scope are flagged as transferred in or out.
+    /// the grade, the class and the major are the ones of the student in the scope
+    async fn get_gpa_by_major_history(
+        &self,
+        terms: &[i64],
+        scope: &RankScope,
+        grade: i32,
+        aggregation: &Aggregation,
+        cohort: Cohort,
+    ) -> Result<Vec<ResultRow>, CustomError> {
+        // the grade is bound as ?1, followed by the ids of the scope, the term ids and the
+        // weights if any
+        let scope_ids = scope.ids();
+        let first_term_param = 2 + scope_ids.len();
+        let weights = aggregation.weights(terms)?;
+        let (cohort_endpoint, other_endpoint) = match cohort {
+            Cohort::OriginalMajor => ("original", "latest"),
+            _ => ("latest", "original"),
+        };
+        let sql_str = format!(
+            r"WITH placed AS (
+                SELECT
+                    academic_records.student_id,
+                    academic_records.class_id,
+                    classes.major_id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY academic_records.student_id
+                        ORDER BY terms.start_year, terms.semester
+                    ) AS original,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY academic_records.student_id
+                        ORDER BY terms.start_year DESC, terms.semester DESC
+                    ) AS latest
+                FROM academic_records
+                JOIN terms ON terms.term_id = academic_records.term_id
+                JOIN classes ON classes.class_id = academic_records.class_id
+            ),
+            endpoints AS (
+                SELECT
+                    student_id,
+                    MAX( CASE WHEN original = 1 THEN major_id END ) AS original_major_id,
+                    MAX( CASE WHEN original = 1 THEN class_id END ) AS original_class_id,
+                    MAX( CASE WHEN latest = 1 THEN major_id END ) AS latest_major_id,
+                    MAX( CASE WHEN latest = 1 THEN class_id END ) AS latest_class_id
+                FROM placed
+                WHERE original = 1 OR latest = 1
+                GROUP BY student_id
+            ),
+            scoped AS (
+                SELECT
+                    endpoints.*,
+                    ( {original_scope} ) AS original_in_scope,
+                    ( {latest_scope} ) AS latest_in_scope
+                FROM endpoints
+            )
+            SELECT
+                classes.class_name AS class,
+                majors.major_name AS major,
+                colleges.college_name AS college,
+                students.student_number AS sno,
+                students.name AS name,
+                {aggregation} AS gpa,
+                COUNT( academic_records.gpa ) AS gpa_terms,
+                scoped.original_major_id,
+                original_majors.major_name AS original_major,
+                scoped.latest_major_id,
+                latest_majors.major_name AS latest_major,
+                scoped.original_in_scope,
+                scoped.latest_in_scope
+            FROM scoped
+            JOIN students ON students.student_id = scoped.student_id
+            JOIN classes ON classes.class_id = CASE
+                WHEN scoped.{cohort}_in_scope THEN scoped.{cohort}_class_id
+                ELSE scoped.{other}_class_id
+            END
+            JOIN majors ON majors.major_id = classes.major_id
+            JOIN colleges ON colleges.college_id = majors.college_id
+            JOIN majors AS original_majors ON original_majors.major_id = scoped.original_major_id
+            JOIN majors AS latest_majors ON latest_majors.major_id = scoped.latest_major_id
+            JOIN academic_records ON academic_records.student_id = scoped.student_id
+                AND academic_records.term_id IN ( {placeholders} )
+            WHERE ( scoped.original_in_scope OR scoped.latest_in_scope )
+            AND classes.enrollment_year = ?1
+            GROUP BY scoped.student_id;",
+            original_scope = scope.sql_condition(
+                "endpoints.original_class_id",
+                "endpoints.original_major_id",
+                2
+            ),
+            latest_scope =
+                scope.sql_condition("endpoints.latest_class_id", "endpoints.latest_major_id", 2),
+            aggregation = aggregation.sql_expression(terms.len(), first_term_param),
+            placeholders = numbered_placeholders(first_term_param, terms.len()),
+            cohort = cohort_endpoint,
+            other = other_endpoint,
+        );
+
+        let mut query = sqlx::query_as(&sql_str).bind(grade);
+        for id in scope_ids {
+            query = query.bind(id);
+        }
+        for term_id in terms {
+            query = query.bind(term_id);
+        }
+        for weight in weights {
+            query = query.bind(weight);
+        }
+        let cohort_rows: Vec<CohortRow> = query.fetch_all(&self.db).await?;
+        let mut result: Vec<ResultRow> = cohort_rows
+            .into_iter()
+            .map(|cohort_row| {
+                let mut row = cohort_row.row;
+                row.incomplete = row.gpa_terms < terms.len() as i64;
+                // a change of major within the scope doesn't move the student in or out of it
+                if cohort_row.original_major_id != cohort_row.latest_major_id
+                    && cohort_row.original_in_scope != cohort_row.latest_in_scope
+                {
+                    if cohort_row.latest_in_scope {
+                        row.transfer = Some(Transfer::In);
+                        row.transfer_major = Some(cohort_row.original_major);
+                    } else {
+                        row.transfer = Some(Transfer::Out);
+                        row.transfer_major = Some(cohort_row.latest_major);
+                    }
+                }
+                row.outside_cohort = match cohort {
+                    Cohort::OriginalMajor => !cohort_row.original_in_scope,
+                    _ => !cohort_row.latest_in_scope,
+                };
+                row
+            })
+            .collect();
+        rank_rows(&mut result, aggregation.compares_incomplete());
+        Ok(result)
+    }
+}
+
+/// the numbered placeholders of a variable-length list, such as `?5, ?6, ?7`
+fn numbered_placeholders(first_param: usize, count: usize) -> String {
+    (first_param..first_param + count)
+        .map(|param| format!("?{}", param))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// escape the `%`, `_` and `\` of the text used in a `LIKE ... ESCAPE '\'` pattern
+fn escape_like(text: &str) -> String {
+    escape_like_chars(text).collect()
+}
+
+/// escape each character of the text used in a `LIKE ... ESCAPE '\'` pattern
+fn escape_like_chars(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.chars().map(|c| match c {
+        '%' | '_' | '\\' => format!("\\{}", c),
+        c => c.to_string(),
+    })
+}
+
+/// the (college number, major name, class name) path identifying a class, since the same
+/// major name may exist in several colleges
+pub type ClassPath = (String, String, String);
+
+/// insert the academic info and return the id maps of the terms and the classes
+///
+/// the majors are resolved within their college and the classes within their major
+pub async fn insert_academic_info<'db_connect>(
+    tx: &mut sqlx::Transaction<'db_connect, Sqlite>,
+    data: &Vec<CollegeData>,
+) -> Result<(HashMap<String, i64>, HashMap<ClassPath, i64>), CustomError> {
+    // create the terms, colleges and majors map
+    let mut terms = HashMap::new();
+    let mut colleges = HashMap::new();
+    let mut majors = HashMap::new();
+    let mut classes = HashMap::new();
+
+    // for each college data
+    for college_data in data {
+        // extract the college data
+        let CollegeData {
+            term_name,
+            college_name,
+            college_number,
+            data: _,
+        } = college_data;
+        // insert the term and college info
+        if let None = terms.get(term_name.as_str()) {
+            let term_id = insert_terms(tx, term_name.as_str()).await?;
+            terms.insert(term_name.as_str().to_string(), term_id);
+        }
+        let college_id = {
+            match colleges.get(college_number.as_str()) {
+                Some(college_id) => *college_id,
+                None => {
+                    let college_id =
+                        insert_college(tx, college_name.as_str(), college_number.as_str()).await?;
+                    colleges.insert(college_number.as_str().to_string(), college_id);
+                    college_id
+                }
+            }
+        };
+        // insert info from each table
+        for table in &college_data.data {
+            let CsvTable {
+                major_name,
+                class_name,
+                enrollment_year,
+                class_number,
+                ..
+            } = table;
+            // insert the major info
+            let major_key = (college_id, major_name.to_string());
+            let major_id = if let Some(id) = majors.get(&major_key) {
+                *id
+            } else {
+                let id = insert_major(tx, major_name.as_str(), college_id).await?;
+                majors.insert(major_key, id);
+                id
+            };
+            // insert the class info
+            let class_path = (
+                college_number.to_string(),
+                major_name.to_string(),
+                class_name.to_string(),
+            );
+            if let Entry::Vacant(entry) = classes.entry(class_path) {
+                let class_id =
+                    insert_class(tx, class_name, major_id, *enrollment_year, *class_number).await?;
+                entry.insert(class_id);
+            }
+        }
+    }
+    Ok((terms, classes))
+}
+
+/// delete the academic records of the loaded term, so that it can be imported again
+async fn delete_term_records(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    term_name: &str,
+) -> Result<(), CustomError> {
+    insert(
+        tx,
+        r"DELETE FROM academic_records WHERE term_id = (SELECT term_id FROM terms WHERE term_name = ?1);",
+        vec![term_name],
+        None,
+    )
+    .await
+}
+
+/// insert the records of all the classes in a college, return the inserted row count
+async fn insert_college_records<'db_connect>(
+    tx: &mut sqlx::Transaction<'db_connect, Sqlite>,
+    data: Vec<CsvTable>,
+    term_id: i64,
+    college_number: &str,
+    classes_map: &HashMap<ClassPath, i64>,
+) -> Result<usize, CustomError> {
+    let mut row_cnt = 0;
+    for table in data {
+        // extract the academic info
+        let CsvTable {
+            records,
+            major_name,
+            class_name,
+            ..
+        } = table;
+        // get the classes id by the full path
+        let class_id = *classes_map
+            .get(&(college_number.to_string(), major_name, class_name.clone()))
+            .ok_or(CustomError::UnknownError(format!(
+                "未知的班级: {}",
+                class_name
+            )))?;
+
+        // insert the academic records
+        insert_csv_row_record(tx, &records, term_id, class_id).await?;
+        row_cnt += records.len();
+    }
+    Ok(row_cnt)
+}
+
+/// insert the csv row record into the database
+/// should be called after the academic info is inserted
+async fn insert_csv_row_record<'db_connect>(
+    tx: &mut sqlx::Transaction<'db_connect, Sqlite>,
+    records: &CsvRecords,
+    term_id: i64,
+    class_id: i64,
+) -> Result<(), CustomError> {
+    for record in records {
+        // insert the student info
+        let student_id = insert_or_ignore_student(tx, &record.sid, &record.name).await?;
+        // the missing gpa is stored as NULL, so that it is never ranked as 0
+        insert_with_retry(tx, || {
+            sqlx::query(
+                r"INSERT OR IGNORE INTO academic_records ( gpa, term_id, class_id, student_id ) VALUES (?1, ?2, ?3, ?4);",
+            )
+            .bind(record.gpa)
+            .bind(term_id)
+            .bind(class_id)
+            .bind(student_id)
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// insert the term with its academic year and semester parsed from the name, which are NULL
+/// if the name is not like `2022-2023-1`
+async fn insert_terms(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    term_name: &str,
+) -> Result<i64, CustomError> {
+    let term = Term::parse(term_name).ok();
+    insert_with_retry(tx, || {
+        sqlx::query(
+            r"INSERT OR IGNORE INTO terms (term_name, start_year, end_year, semester) VALUES (?1, ?2, ?3, ?4);",
+        )
+        .bind(term_name)
+        .bind(term.map(|t| t.start_year))
+        .bind(term.map(|t| t.end_year))
+        .bind(term.map(|t| t.semester))
+    })
+    .await?;
+
+    let term_id = get_record_id(
+        tx,
+        r"SELECT term_id FROM terms WHERE term_name = ?1;",
+        vec![term_name],
+    )
+    .await?;
+
+    Ok(term_id)
+}
+
+async fn insert_college(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    college_name: &str,
+    college_number: &str,
+) -> Result<i64, CustomError> {
+    insert(
+        tx,
+        r"INSERT INTO colleges (college_name, college_number) VALUES (?1, ?2) ON CONFLICT(college_number) DO UPDATE SET college_name = excluded.college_name;",
+        vec![college_name, college_number],
+        None,
+    )
+    .await?;
+
+    let college_id = get_record_id(
+        tx,
+        r"SELECT college_id FROM colleges WHERE college_number = ?1;",
+        vec![college_number],
+    )
+    .await?;
+
+    Ok(college_id)
+}
+
+/// insert the major into the college, the same name in another college is another major
+async fn insert_major(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    major_name: &str,
+    college_id: i64,
+) -> Result<i64, CustomError> {
+    insert_with_retry(tx, || {
+        sqlx::query(r"INSERT OR IGNORE INTO majors (major_name, college_id) VALUES (?1, ?2);")
+            .bind(major_name)
+            .bind(college_id)
+    })
+    .await?;
+
+    let major_id: i64 = sqlx::query_scalar(
+        r"SELECT major_id FROM majors WHERE college_id = ?1 AND major_name = ?2;",
+    )
+    .bind(college_id)
+    .bind(major_name)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(major_id)
+}
+
+/// insert the class into the major, the same name in another major is another class
+async fn insert_class(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    class_name: &str,
+    major_id: i64,
+    enrollment_year: i32,
+    class_number: i32,
+) -> Result<i64, CustomError> {
+    insert_with_retry(tx, || {
+        sqlx::query(
+            r"INSERT OR IGNORE INTO classes (class_name, major_id, enrollment_year, class_number) VALUES (?1, ?2, ?3, ?4);",
+        )
+        .bind(class_name)
+        .bind(major_id)
+        .bind(enrollment_year)
+        .bind(class_number)
+    })
+    .await?;
+
+    let class_id: i64 = sqlx::query_scalar(
+        r"SELECT class_id FROM classes WHERE major_id = ?1 AND class_name = ?2;",
+    )
+    .bind(major_id)
+    .bind(class_name)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(class_id)
+}
+
+/// delete the classes without any record and then the majors without any class
+///
+/// the records of a replaced term are linked to the classes of the import, so the classes
+/// they were wrongly merged into before majors were scoped to their college are left empty
+async fn delete_unused_classes(tx: &mut sqlx::Transaction<'_, Sqlite>) -> Result<(), CustomError> {
+    sqlx::query(
+        r"DELETE FROM classes WHERE class_id NOT IN ( SELECT DISTINCT class_id FROM academic_records );",
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(r"DELETE FROM majors WHERE major_id NOT IN ( SELECT major_id FROM classes );")
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn insert_or_ignore_student(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    student_number: &str,
+    name: &str,
+) -> Result<i64, CustomError> {
+    insert_with_retry(tx, || {
+        sqlx::query(r"INSERT OR IGNORE INTO students (student_number, name) VALUES (?1, ?2);")
+            .bind(student_number)
+            .bind(name)
+    })
+    .await?;
+
+    let student_id = get_record_id(
+        tx,
+        r"SELECT student_id FROM students WHERE student_number = ?1;",
+        vec![student_number],
+    )
+    .await?;
+
+    Ok(student_id)
+}
+
+/// get the record id
+///
+/// # Arguments
+///
+/// * `tx` - the database transaction
+/// * `sql_statement` - the sql statement
+/// * `values` - the values
+///
+/// # Returns
+///
+/// the record id
+///
+/// # Errors
+///
+/// return the error if the operation failed
+async fn get_record_id<'db_connection>(
+    tx: &mut sqlx::Transaction<'db_connection, Sqlite>,
+    sql_statement: &str,
+    values: Vec<&str>,
+) -> Result<i64, CustomError> {
+    // create the sql statement
+    let mut sql = sqlx::query(sql_statement);
+    for value in &values {
+        sql = sql.bind(value);
+    }
+    // get the record id
+    let row = sql.fetch_one(&mut **tx).await?;
+    let record_id = row.try_get(0)?;
+    Ok(record_id)
+}
+
+/// insert or ignore the record, retry while the database is locked
+///
+/// # Arguments
+///
+/// * `tx` - the database transaction
+/// * `build_query` - build the query with its bound values, called again on each retry
+///
+/// # Returns
+///
+/// return the result
+///
+/// # Errors
+///
+/// return the error if the operation failed
+async fn insert_with_retry<'db_connection, 'q>(
+    tx: &mut sqlx::Transaction<'db_connection, Sqlite>,
+    build_query: impl Fn() -> Query<'q, Sqlite, SqliteArguments<'q>>,
+) -> Result<(), CustomError> {
+    let mut retries = 20; // todo: move to the configuration
+    let retry_delay = tokio::time::Duration::from_millis(100); // todo: move to the configuration
+
+    // loop until the operation is successful
+    loop {
+        let result = build_query().execute(&mut **tx).await;
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) if e.to_string().contains("database is locked") => {
+                retries -= 1;
+                tokio::time::sleep(retry_delay).await; // todo: move to the configuration
+                if retries == 0 {
+                    return Err(e.into());
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+async fn insert<'db_connection>(
+    tx: &mut sqlx::Transaction<'db_connection, Sqlite>,
+    sql_statement: &str,
+    values: Vec<&str>,
+    id: Option<i64>,
+) -> Result<(), CustomError> {
+    // create the sql statement
+    let mut sql = sqlx::query(sql_statement);
+    for value in &values {
+        sql = sql.bind(value);
+    }
+
+    if let Some(id) = id {
+        sql = sql.bind(id);
+    }
+    sql.execute(&mut **tx).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::csv_processor::RowRecord;
+
+    use super::*;
+    use std::error::Error;
+
+    async fn build_app_state() -> Result<AppState, Box<dyn Error>> {
+        // get the cargo project root directory
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        // create the data directory if it doesn't exist
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+        // append the database file name to the path
+        path.push("com.neau.gpa.getter");
+        if !path.exists() {
+            std::fs::create_dir_all(&path)
+                .map_err(|e| format!("Failed to create data directory: {}", e))?;
+        }
+        path.push("data.db");
+
+        // test if the database file exists
+        if !path.exists() {
+            // create the database file if it doesn't exist
+            std::fs::File::create(&path)
+                .map_err(|e| format!("Failed to create database file: {}", e))?;
+        }
+
+        // connect the database
+        let pool = SqlitePool::connect(&format!("sqlite://{}", path.to_str().unwrap()))
+            .await
+            .map_err(|e| format!("Failed to connect to the database: {}", e))?;
+        // use the migration feature of sqlx to create the table
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        Ok(AppState { db: pool })
+    }
+
+    /// build the application state on a fresh database in a temporary directory
+    async fn build_temp_app_state() -> Result<(AppState, tempfile::TempDir), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let app_state = AppState::open(&dir.path().join("data.db")).await?;
+
+        Ok((app_state, dir))
+    }
+
+    /// create the college data of a single class
+    fn college_data(term_name: &str, records: Vec<(&str, &str, f64)>) -> CollegeData {
+        CollegeData {
+            term_name: Arc::new(term_name.to_string()),
+            college_name: Arc::new("Engineering".to_string()),
+            college_number: Arc::new("01".to_string()),
+            data: vec![CsvTable {
+                records: records
+                    .into_iter()
+                    .map(|(sid, name, gpa)| RowRecord {
+                        sid: sid.to_string(),
+                        name: name.to_string(),
+                        gpa: Some(gpa),
+                    })
+                    .collect(),
+                major_name: "Computer Science".to_string(),
+                class_name: "Computer Science2101".to_string(),
+                enrollment_year: 2021,
+                class_number: 1,
+                has_gpa_column: true,
+            }],
+        }
+    }
+
+    async fn count_records(app_state: &AppState) -> i64 {
+        sqlx::query_scalar(r"SELECT COUNT(*) FROM academic_records;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_insert_academic_info() {
+        let app_state = build_app_state().await.unwrap();
+        let data = vec![college_data("Fall 2021", vec![])];
+
+        let mut tx = app_state.db.begin().await.unwrap();
+        let result = insert_academic_info(&mut tx, &data).await;
+        tx.commit().await.unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_csv_data() {
+        let app_state = build_app_state().await.unwrap();
+        let csv_data = vec![college_data(
+            "Fall 2021",
+            vec![("12345", "John Doe", 4.0_f64)],
+        )];
+
+        let result = app_state
+            .set(
+                csv_data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn set_twice_does_not_duplicate_records() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let records = vec![("2021001", "Alice", 3.5), ("2021002", "Bob", 3.0)];
+
+        for _ in 0..2 {
+            app_state
+                .set(
+                    vec![college_data("2021-2022-1", records.clone())],
+                    MergeMode::Replace,
+                    &ProgressReporter::default(),
+                    &CancellationToken::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(count_records(&app_state).await, 2);
+    }
+
+    #[tokio::test]
+    async fn set_replace_mode_replaces_loaded_term() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        app_state
+            .set(
+                vec![college_data("2021-2022-1", vec![("2021001", "Alice", 3.5)])],
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let data = vec![
+            college_data("2021-2022-1", vec![("2021001", "Alice", 3.8)]),
+            college_data("2021-2022-2", vec![("2021001", "Alice", 3.6)]),
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let gpa: f64 = sqlx::query_scalar(
+            r"SELECT gpa FROM academic_records JOIN terms ON terms.term_id = academic_records.term_id WHERE term_name = '2021-2022-1';",
+        )
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+        assert_eq!(gpa, 3.8);
+        assert_eq!(count_records(&app_state).await, 2);
+    }
+
+    #[tokio::test]
+    async fn set_skip_mode_only_adds_new_terms() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        app_state
+            .set(
+                vec![college_data("2021-2022-1", vec![("2021001", "Alice", 3.5)])],
+                MergeMode::Skip,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let data = vec![
+            college_data("2021-2022-1", vec![("2021001", "Alice", 3.8)]),
+            college_data("2021-2022-2", vec![("2021001", "Alice", 3.6)]),
+        ];
+        let result = app_state
+            .set(
+                data,
+                MergeMode::Skip,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let gpa: f64 = sqlx::query_scalar(
+            r"SELECT gpa FROM academic_records JOIN terms ON terms.term_id = academic_records.term_id WHERE term_name = '2021-2022-1';",
+        )
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+        assert_eq!(gpa, 3.5);
+        assert_eq!(count_records(&app_state).await, 2);
+        assert_eq!(result.skipped_terms, vec!["2021-2022-1"]);
+    }
+
+    #[tokio::test]
+    async fn set_failure_leaves_loaded_data_untouched() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let data = vec![college_data("2021-2022-1", vec![("2021001", "Alice", 3.5)])];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        // make the insertion of the second college fail
+        sqlx::query(
+            r"CREATE TRIGGER reject_gpa BEFORE INSERT ON academic_records WHEN NEW.gpa > 100
+            BEGIN SELECT RAISE(ABORT, 'gpa out of range'); END;",
+        )
+        .execute(&app_state.db)
+        .await
+        .unwrap();
+        let mut other = college_data("2021-2022-2", vec![("2021002", "Bob", 120.0)]);
+        other.college_name = Arc::new("Science".to_string());
+        other.college_number = Arc::new("02".to_string());
+        let data = vec![
+            college_data("2021-2022-1", vec![("2021001", "Alice", 3.8)]),
+            college_data("2021-2022-2", vec![("2021001", "Alice", 3.6)]),
+            other,
+        ];
+        let result = app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await;
+
+        assert!(result.is_err());
+        let gpa: f64 = sqlx::query_scalar(r"SELECT gpa FROM academic_records;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+        assert_eq!(gpa, 3.5);
+        assert_eq!(count_records(&app_state).await, 1);
+        let terms = app_state.get_terms().await.unwrap();
+        assert_eq!(terms.len(), 1);
+        let colleges = app_state.get_colleges().await.unwrap();
+        assert_eq!(colleges.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_gpa_ranks_students() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let data = vec![college_data(
+            "2021-2022-1",
+            vec![
+                ("2021001", "Alice", 85.0),
+                ("2021002", "Bob", 90.0),
+                ("2021003", "Carol", 85.0),
+            ],
+        )];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_id: i64 = sqlx::query_scalar(r"SELECT term_id FROM terms;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+        let major_id: i64 = sqlx::query_scalar(r"SELECT major_id FROM majors;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+
+        let rows = app_state
+            .get_gpa(
+                &[term_id],
+                &RankScope::Major { major_id },
+                2021,
+                &Aggregation::Sum,
+                Cohort::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rows[0].sno, "2021002");
+        let ranks: Vec<Option<usize>> = rows.iter().map(|row| row.competition_rank).collect();
+        assert_eq!(ranks, vec![Some(1), Some(2), Some(2)]);
+        assert!(rows.iter().all(|row| row.cohort_size == 3));
+    }
+
+    #[tokio::test]
+    async fn get_gpa_flags_incomplete_terms() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let mut first_term = college_data(
+            "2021-2022-1",
+            vec![("2021001", "Alice", 85.0), ("2021002", "Bob", 0.0)],
+        );
+        first_term.data[0].records[1].gpa = None;
+        let data = vec![
+            first_term,
+            college_data(
+                "2021-2022-2",
+                vec![("2021001", "Alice", 80.0), ("2021002", "Bob", 90.0)],
+            ),
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let missing: i64 =
+            sqlx::query_scalar(r"SELECT COUNT(*) FROM academic_records WHERE gpa IS NULL;")
+                .fetch_one(&app_state.db)
+                .await
+                .unwrap();
+        assert_eq!(missing, 1);
+        let term_ids: Vec<i64> =
+            sqlx::query_scalar(r"SELECT term_id FROM terms ORDER BY term_name;")
+                .fetch_all(&app_state.db)
+                .await
+                .unwrap();
+        let major_id: i64 = sqlx::query_scalar(r"SELECT major_id FROM majors;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+
+        let rows = app_state
+            .get_gpa(
+                &term_ids,
+                &RankScope::Major { major_id },
+                2021,
+                &Aggregation::Sum,
+                Cohort::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rows[0].sno, "2021001");
+        assert_eq!(rows[0].gpa, Some(165.0));
+        assert_eq!(rows[0].competition_rank, Some(1));
+        assert!(!rows[0].incomplete);
+        assert_eq!(rows[1].sno, "2021002");
+        assert_eq!(rows[1].gpa, Some(90.0));
+        assert_eq!(rows[1].gpa_terms, 1);
+        assert!(rows[1].incomplete);
+        assert_eq!(rows[1].competition_rank, None);
+        assert_eq!(rows[0].cohort_size, 1);
+    }
+
+    #[tokio::test]
+    async fn get_gpa_aggregates_terms() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let data = vec![
+            college_data("2021-2022-1", vec![("2021001", "Alice", 80.0)]),
+            college_data(
+                "2021-2022-2",
+                vec![("2021001", "Alice", 90.0), ("2021002", "Bob", 88.0)],
+            ),
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_ids: Vec<i64> =
+            sqlx::query_scalar(r"SELECT term_id FROM terms ORDER BY term_name;")
+                .fetch_all(&app_state.db)
+                .await
+                .unwrap();
+        let major_id: i64 = sqlx::query_scalar(r"SELECT major_id FROM majors;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+        let query = |aggregation: Aggregation| {
+            let app_state = app_state.clone();
+            let term_ids = term_ids.clone();
+            async move {
+                app_state
+                    .get_gpa(
+                        &term_ids,
+                        &RankScope::Major { major_id },
+                        2021,
+                        &aggregation,
+                        Cohort::default(),
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // the mean over the available terms ranks Bob who missed a term
+        let rows = query(Aggregation::Mean).await;
+        assert_eq!(rows[0].sno, "2021002");
+        assert_eq!(rows[0].gpa, Some(88.0));
+        assert_eq!(rows[0].gpa_terms, 1);
+        assert_eq!(rows[0].competition_rank, Some(1));
+        assert_eq!(rows[1].gpa, Some(85.0));
+
+        let weights = HashMap::from([(term_ids[0], 1.0), (term_ids[1], 3.0)]);
+        let rows = query(Aggregation::WeightedMean { weights }).await;
+        assert_eq!(rows[0].gpa, Some(88.0));
+        assert_eq!(rows[1].sno, "2021001");
+        assert_eq!(rows[1].gpa, Some(87.5));
+        assert_eq!(rows[1].gpa_terms, 2);
+
+        let weights = HashMap::from([(term_ids[0], 1.0)]);
+        let result = app_state
+            .get_gpa(
+                &term_ids,
+                &RankScope::Major { major_id },
+                2021,
+                &Aggregation::WeightedMean { weights },
+                Cohort::default(),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn get_gpa_ranks_by_latest_or_original_major() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        // Carol transfers from Computer Science to Software in the second term
+        let mut second = college_data(
+            "2021-2022-2",
+            vec![("2021001", "Alice", 80.0), ("2021002", "Bob", 70.0)],
+        );
+        second.data.push(CsvTable {
+            records: vec![RowRecord {
+                sid: "2021003".to_string(),
+                name: "Carol".to_string(),
+                gpa: Some(95.0),
+            }],
+            major_name: "Software".to_string(),
+            class_name: "Software2101".to_string(),
+            enrollment_year: 2021,
+            class_number: 1,
+            has_gpa_column: true,
+        });
+        let data = vec![
+            college_data(
+                "2021-2022-1",
+                vec![
+                    ("2021001", "Alice", 80.0),
+                    ("2021002", "Bob", 70.0),
+                    ("2021003", "Carol", 90.0),
+                ],
+            ),
+            second,
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_ids: Vec<i64> =
+            sqlx::query_scalar(r"SELECT term_id FROM terms ORDER BY term_name;")
+                .fetch_all(&app_state.db)
+                .await
+                .unwrap();
+        let major_id = |name: &'static str| {
+            let app_state = app_state.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(r"SELECT major_id FROM majors WHERE major_name = ?;")
+                    .bind(name)
+                    .fetch_one(&app_state.db)
+                    .await
+                    .unwrap()
+            }
+        };
+        let computer_science = major_id("Computer Science").await;
+        let software = major_id("Software").await;
+        let query = |major_id: i64, cohort: Cohort| {
+            let app_state = app_state.clone();
+            let term_ids = term_ids.clone();
+            async move {
+                app_state
+                    .get_gpa(
+                        &term_ids,
+                        &RankScope::Major { major_id },
+                        2021,
+                        &Aggregation::Sum,
+                        cohort,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // ranked in the original major, Carol is flagged as transferred out
+        let rows = query(computer_science, Cohort::OriginalMajor).await;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].sno, "2021003");
+        assert_eq!(rows[0].gpa, Some(185.0));
+        assert_eq!(rows[0].class, "Computer Science2101");
+        assert_eq!(rows[0].competition_rank, Some(1));
+        assert_eq!(rows[0].transfer, Some(Transfer::Out));
+        assert_eq!(rows[0].transfer_major.as_deref(), Some("Software"));
+        assert_eq!(rows[1].transfer, None);
+        assert_eq!(rows[2].cohort_size, 3);
+
+        // ranked in the latest major, Carol is listed in Computer Science but not ranked
+        let rows = query(computer_science, Cohort::LatestMajor).await;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].sno, "2021001");
+        assert_eq!(rows[0].cohort_size, 2);
+        assert_eq!(rows[2].sno, "2021003");
+        assert!(rows[2].outside_cohort);
+        assert_eq!(rows[2].competition_rank, None);
+
+        // and ranked in Software, flagged as transferred in
+        let rows = query(software, Cohort::LatestMajor).await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].class, "Software2101");
+        assert_eq!(rows[0].competition_rank, Some(1));
+        assert_eq!(rows[0].transfer, Some(Transfer::In));
+        assert_eq!(rows[0].transfer_major.as_deref(), Some("Computer Science"));
+
+        // the major of the last selected term leaves Carol out of Computer Science
+        let rows = query(computer_science, Cohort::LastSelectedTerm).await;
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_class_changes_between_terms() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        // Carol moves to Software, Bob moves to another class of the same major
+        let mut second = college_data("2021-2022-2", vec![("2021001", "Alice", 80.0)]);
+        for (sid, name, major, class, class_number) in [
+            (
+                "2021002",
+                "Bob",
+                "Computer Science",
+                "Computer Science2102",
+                2,
+            ),
+            ("2021003", "Carol", "Software", "Software2101", 1),
+        ] {
+            second.data.push(CsvTable {
+                records: vec![RowRecord {
+                    sid: sid.to_string(),
+                    name: name.to_string(),
+                    gpa: Some(90.0),
+                }],
+                major_name: major.to_string(),
+                class_name: class.to_string(),
+                enrollment_year: 2021,
+                class_number,
+                has_gpa_column: true,
+            });
+        }
+        let data = vec![
+            college_data(
+                "2021-2022-1",
+                vec![
+                    ("2021001", "Alice", 80.0),
+                    ("2021002", "Bob", 70.0),
+                    ("2021003", "Carol", 90.0),
+                ],
+            ),
+            second,
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let software: i64 =
+            sqlx::query_scalar(r"SELECT major_id FROM majors WHERE major_name = 'Software';")
+                .fetch_one(&app_state.db)
+                .await
+                .unwrap();
+
+        let changes = app_state.get_class_changes(None, Some(2021)).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].student_number, "2021002");
+        assert_eq!(changes[0].from_term_name, "2021-2022-1");
+        assert_eq!(changes[0].term_name, "2021-2022-2");
+        assert_eq!(changes[0].from_class, "Computer Science2101");
+        assert_eq!(changes[0].to_class, "Computer Science2102");
+        assert!(!changes[0].major_changed);
+        assert_eq!(changes[1].student_number, "2021003");
+        assert_eq!(changes[1].from_major, "Computer Science");
+        assert_eq!(changes[1].to_major, "Software");
+        assert!(changes[1].major_changed);
+
+        let changes = app_state
+            .get_class_changes(Some(software), None)
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name, "Carol");
+
+        let changes = app_state.get_class_changes(None, Some(2022)).await.unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_grades_and_filter_by_enrollment_year() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let mut data = college_data("2022-2023-1", vec![("2021001", "Alice", 80.0)]);
+        data.data.push(CsvTable {
+            records: vec![RowRecord {
+                sid: "2022001".to_string(),
+                name: "Bob".to_string(),
+                gpa: Some(90.0),
+            }],
+            major_name: "Computer Science".to_string(),
+            class_name: "Computer Science2203".to_string(),
+            enrollment_year: 2022,
+            class_number: 3,
+            has_gpa_column: true,
+        });
+        app_state
+            .set(
+                vec![data],
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_id = app_state.get_terms().await.unwrap()[0].term_id;
+        let major_id: i64 = sqlx::query_scalar(r"SELECT major_id FROM majors;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            app_state.get_grades(Some(major_id)).await.unwrap(),
+            vec![2022, 2021]
+        );
+        let classes = app_state.get_classes(major_id, 2022).await.unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].class_name, "Computer Science2203");
+
+        let rows = app_state
+            .get_gpa(
+                &[term_id],
+                &RankScope::Major { major_id },
+                2022,
+                &Aggregation::Sum,
+                Cohort::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "Bob");
+        assert!(app_state
+            .get_grades(Some(major_id + 1))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn majors_are_scoped_to_their_college() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        // a major of the same name in another college, with a class of another number
+        let data = || {
+            let engineering = college_data("2021-2022-1", vec![("2021001", "Alice", 80.0)]);
+            let mut science = college_data("2021-2022-1", vec![("2021002", "Bob", 90.0)]);
+            science.college_name = Arc::new("Science".to_string());
+            science.college_number = Arc::new("02".to_string());
+            science.data[0].class_name = "Computer Science2102".to_string();
+            science.data[0].class_number = 2;
+            vec![engineering, science]
+        };
+        app_state
+            .set(
+                data(),
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_ids: Vec<i64> = vec![app_state.get_terms().await.unwrap()[0].term_id];
+        let major_of = |college_number: &'static str| {
+            let db = app_state.db.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(
+                    r"SELECT major_id FROM majors JOIN colleges USING (college_id) WHERE college_number = ?1;",
+                )
+                .bind(college_number)
+                .fetch_one(&db)
+                .await
+                .unwrap()
+            }
+        };
+        let (engineering_major, science_major) = (major_of("01").await, major_of("02").await);
+        assert_ne!(engineering_major, science_major);
+        let names = |rows: Vec<ResultRow>| rows.into_iter().map(|r| r.name).collect::<Vec<_>>();
+        let query = |major_id| {
+            let app_state = app_state.clone();
+            let term_ids = term_ids.clone();
+            async move {
+                app_state
+                    .get_gpa(
+                        &term_ids,
+                        &RankScope::Major { major_id },
+                        2021,
+                        &Aggregation::Sum,
+                        Cohort::default(),
+                    )
+                    .await
+            }
+        };
+        assert_eq!(
+            names(query(engineering_major).await.unwrap()),
+            vec!["Alice"]
+        );
+        assert_eq!(names(query(science_major).await.unwrap()), vec!["Bob"]);
+
+        // merge Science into Engineering the way the global unique names did
+        for sql in [
+            r"UPDATE classes SET major_id = ?1 WHERE major_id = ?2;",
+            r"DELETE FROM majors WHERE major_id = ?2;",
+        ] {
+            sqlx::query(sql)
+                .bind(engineering_major)
+                .bind(science_major)
+                .execute(&app_state.db)
+                .await
+                .unwrap();
+        }
+        assert_eq!(query(engineering_major).await.unwrap().len(), 2);
+
+        // replacing the term splits them again and drops the class left empty
+        app_state
+            .set(
+                data(),
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let science_major = major_of("02").await;
+        assert_eq!(
+            names(query(engineering_major).await.unwrap()),
+            vec!["Alice"]
+        );
+        assert_eq!(names(query(science_major).await.unwrap()), vec!["Bob"]);
+        let classes = app_state
+            .get_classes(engineering_major, 2021)
+            .await
+            .unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].class_name, "Computer Science2101");
+    }
+
+    #[tokio::test]
+    async fn get_gpa_ranks_across_scopes() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let table = |major: &str, class: &str, records: Vec<(&str, &str, f64)>| {
+            let mut table = college_data("", records).data.remove(0);
+            table.major_name = major.to_string();
+            table.class_name = class.to_string();
+            table.enrollment_year = 2000 + class[class.len() - 4..][..2].parse::<i32>().unwrap();
+            table
+        };
+        let college = |term_name: &str, name: &str, number: &str, data: Vec<CsvTable>| {
+            let mut college = college_data(term_name, vec![]);
+            college.college_name = Arc::new(name.to_string());
+            college.college_number = Arc::new(number.to_string());
+            college.data = data;
+            college
+        };
+        // Carol moves to Computer Science within Engineering, Alice moves to Math in Science
+        let data = vec![
+            college(
+                "2021-2022-1",
+                "Engineering",
+                "01",
+                vec![
+                    table(
+                        "Computer Science",
+                        "Computer Science2101",
+                        vec![("2021001", "Alice", 80.0), ("2021002", "Bob", 90.0)],
+                    ),
+                    table("Software", "Software2101", vec![("2021003", "Carol", 85.0)]),
+                    table(
+                        "Computer Science",
+                        "Computer Science2201",
+                        vec![("2022001", "Eve", 99.0)],
+                    ),
+                ],
+            ),
+            college(
+                "2021-2022-1",
+                "Science",
+                "02",
+                vec![table("Math", "Math2101", vec![("2021004", "Dave", 95.0)])],
+            ),
+            college(
+                "2021-2022-2",
+                "Engineering",
+                "01",
+                vec![table(
+                    "Computer Science",
+                    "Computer Science2101",
+                    vec![("2021002", "Bob", 90.0), ("2021003", "Carol", 85.0)],
+                )],
+            ),
+            college(
+                "2021-2022-2",
+                "Science",
+                "02",
+                vec![table(
+                    "Math",
+                    "Math2101",
+                    vec![("2021004", "Dave", 95.0), ("2021001", "Alice", 80.0)],
+                )],
+            ),
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_ids: Vec<i64> = app_state
+            .get_terms()
+            .await
+            .unwrap()
+            .iter()
+            .map(|term| term.term_id)
+            .collect();
+        let major_of = |major_name: &'static str| {
+            let db = app_state.db.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(r"SELECT major_id FROM majors WHERE major_name = ?1;")
+                    .bind(major_name)
+                    .fetch_one(&db)
+                    .await
+                    .unwrap()
+            }
+        };
+        let (computer_science, software) = (
+            major_of("Computer Science").await,
+            major_of("Software").await,
+        );
+        let engineering = app_state.get_colleges().await.unwrap()[0].college_id;
+        let class_id = app_state.get_classes(computer_science, 2021).await.unwrap()[0].class_id;
+        let query = |terms: Vec<i64>, scope: RankScope, cohort: Cohort| {
+            let app_state = app_state.clone();
+            async move {
+                app_state
+                    .get_gpa(&terms, &scope, 2021, &Aggregation::Sum, cohort)
+                    .await
+                    .unwrap()
+            }
+        };
+        let names = |rows: &[ResultRow]| rows.iter().map(|r| r.name.clone()).collect::<Vec<_>>();
+
+        let first = vec![term_ids[0]];
+        let scoped = |scope| query(first.clone(), scope, Cohort::default());
+        let rows = scoped(RankScope::Class { class_id }).await;
+        assert_eq!(names(&rows), vec!["Bob", "Alice"]);
+        let rows = scoped(RankScope::Major {
+            major_id: computer_science,
+        })
+        .await;
+        assert_eq!(names(&rows), vec!["Bob", "Alice"]);
+        let rows = scoped(RankScope::Majors {
+            major_ids: vec![computer_science, software],
+        })
+        .await;
+        assert_eq!(names(&rows), vec!["Bob", "Carol", "Alice"]);
+        assert_eq!(rows[1].major, "Software");
+        assert_eq!(rows[1].college, "Engineering");
+        let rows = scoped(RankScope::College {
+            college_id: engineering,
+        })
+        .await;
+        assert_eq!(names(&rows), vec!["Bob", "Carol", "Alice"]);
+        assert_eq!(rows[2].competition_rank, Some(3));
+        let rows = scoped(RankScope::Grade).await;
+        assert_eq!(names(&rows), vec!["Dave", "Bob", "Carol", "Alice"]);
+        assert_eq!(rows[0].major, "Math");
+        assert_eq!(rows[0].college, "Science");
+        let empty = app_state
+            .get_gpa(
+                &first,
+                &RankScope::Majors { major_ids: vec![] },
+                2021,
+                &Aggregation::Sum,
+                Cohort::default(),
+            )
+            .await;
+        assert!(empty.is_err());
+
+        // several terms are placed by the latest term, where Alice is in Science
+        let college_scope = RankScope::College {
+            college_id: engineering,
+        };
+        let rows = query(term_ids.clone(), college_scope.clone(), Cohort::default()).await;
+        assert_eq!(names(&rows), vec!["Bob", "Carol"]);
+        assert_eq!(rows[1].gpa, Some(170.0));
+        assert_eq!(rows[1].major, "Computer Science");
+        let rows = query(term_ids.clone(), RankScope::Grade, Cohort::default()).await;
+        assert_eq!(names(&rows), vec!["Dave", "Bob", "Carol", "Alice"]);
+
+        // Carol's transfer within the college is not flagged, Alice's is
+        let rows = query(
+            term_ids.clone(),
+            college_scope.clone(),
+            Cohort::OriginalMajor,
+        )
+        .await;
+        assert_eq!(names(&rows), vec!["Bob", "Carol", "Alice"]);
+        assert_eq!(rows[1].transfer, None);
+        assert_eq!(rows[2].transfer, Some(Transfer::Out));
+        assert_eq!(rows[2].transfer_major.as_deref(), Some("Math"));
+        assert_eq!(rows[2].major, "Computer Science");
+        assert!(!rows[2].outside_cohort);
+        let rows = query(term_ids, college_scope, Cohort::LatestMajor).await;
+        assert_eq!(names(&rows), vec!["Bob", "Carol", "Alice"]);
+        assert!(rows[2].outside_cohort);
+        assert_eq!(rows[2].competition_rank, None);
+    }
+
+    #[test]
+    fn rank_scope_from_json() {
+        let scope: RankScope =
+            serde_json::from_str(r#"{"kind":"majors","majorIds":[1,2]}"#).unwrap();
+        assert_eq!(
+            scope,
+            RankScope::Majors {
+                major_ids: vec![1, 2]
+            }
+        );
+        let scope: RankScope = serde_json::from_str(r#"{"kind":"grade"}"#).unwrap();
+        assert_eq!(scope, RankScope::Grade);
+    }
+
+    #[tokio::test]
+    async fn terms_are_ordered_chronologically() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        // Carol moves to Software in the later academic year, which is imported first
+        let mut later = college_data("2022-2023-1", vec![("2021001", "Alice", 80.0)]);
+        later.data.push(CsvTable {
+            records: vec![RowRecord {
+                sid: "2021003".to_string(),
+                name: "Carol".to_string(),
+                gpa: Some(95.0),
+            }],
+            major_name: "Software".to_string(),
+            class_name: "Software2101".to_string(),
+            enrollment_year: 2021,
+            class_number: 1,
+            has_gpa_column: true,
+        });
+        for data in [
+            vec![later],
+            vec![
+                college_data(
+                    "2021-2022-3",
+                    vec![("2021001", "Alice", 70.0), ("2021003", "Carol", 90.0)],
+                ),
+                college_data(
+                    "2021-2022-2",
+                    vec![("2021001", "Alice", 60.0), ("2021003", "Carol", 90.0)],
+                ),
+            ],
+        ] {
+            app_state
+                .set(
+                    data,
+                    MergeMode::Skip,
+                    &ProgressReporter::default(),
+                    &CancellationToken::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let terms = app_state.get_terms().await.unwrap();
+        let names: Vec<&str> = terms.iter().map(|t| t.term_name.as_str()).collect();
+        assert_eq!(names, vec!["2021-2022-2", "2021-2022-3", "2022-2023-1"]);
+        assert_eq!(
+            (terms[1].start_year, terms[1].end_year, terms[1].semester),
+            (Some(2021), Some(2022), Some(3))
+        );
+
+        // the latest term decides the students, not the last argument
+        let major_id: i64 = sqlx::query_scalar(
+            r"SELECT major_id FROM majors WHERE major_name = 'Computer Science';",
+        )
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+        let reversed: Vec<i64> = terms.iter().rev().map(|t| t.term_id).collect();
+        let rows = app_state
+            .get_gpa(
+                &reversed,
+                &RankScope::Major { major_id },
+                2021,
+                &Aggregation::Mean,
+                Cohort::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "Alice");
+        assert_eq!(rows[0].gpa, Some(70.0));
+    }
+
+    #[tokio::test]
+    async fn resolve_term_presets() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let data = ["2021-2022-1", "2021-2022-2", "2021-2022-3", "2022-2023-1"]
+            .into_iter()
+            .map(|term_name| college_data(term_name, vec![("2021001", "Alice", 80.0)]))
+            .collect();
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let ids: HashMap<String, i64> = app_state
+            .get_terms()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.term_name, t.term_id))
+            .collect();
+        let resolve = |terms: Vec<i64>, preset, grade| {
+            let app_state = &app_state;
+            async move { app_state.resolve_terms(&terms, preset, grade).await }
+        };
+        let names = |term_ids: Vec<i64>| {
+            term_ids
+                .into_iter()
+                .map(|id| ids.iter().find(|(_, v)| **v == id).unwrap().0.clone())
+                .collect::<Vec<String>>()
+        };
+
+        let year = Some(TermPreset::AcademicYear { year: 2021 });
+        let terms = resolve(vec![], year, None).await.unwrap();
+        assert_eq!(names(terms), vec!["2021-2022-1", "2021-2022-2"]);
+        // the summer term is only selected explicitly
+        let terms = resolve(vec![ids["2021-2022-3"]], year, None).await.unwrap();
+        assert_eq!(
+            names(terms),
+            vec!["2021-2022-1", "2021-2022-2", "2021-2022-3"]
+        );
+        let terms = resolve(vec![], Some(TermPreset::SinceEnrollment), Some(2022))
+            .await
+            .unwrap();
+        assert_eq!(names(terms), vec!["2022-2023-1"]);
+        let terms = resolve(vec![], Some(TermPreset::LastTerms { count: 2 }), None)
+            .await
+            .unwrap();
+        assert_eq!(names(terms), vec!["2021-2022-2", "2022-2023-1"]);
+        // the explicit terms are kept as they are without a preset
+        let terms = resolve(vec![ids["2022-2023-1"], ids["2021-2022-1"]], None, None)
+            .await
+            .unwrap();
+        assert_eq!(names(terms), vec!["2022-2023-1", "2021-2022-1"]);
+
+        for (preset, grade) in [
+            (None, None),
+            (Some(TermPreset::SinceEnrollment), None),
+            (Some(TermPreset::LastTerms { count: 0 }), None),
+            (Some(TermPreset::AcademicYear { year: 2030 }), None),
+        ] {
+            assert!(matches!(
+                resolve(vec![], preset, grade).await,
+                Err(CustomError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn hostile_names_are_bound_not_spliced() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let hostile = "Robert'); DROP TABLE students; --";
+        let mut data = college_data(
+            "2021-2022-1",
+            vec![("2021001", "O'Brien", 80.0), ("2021002", hostile, 90.0)],
+        );
+        data.data[0].major_name = "Computer' Science".to_string();
+        data.data[0].class_name = "Computer' Science2101".to_string();
+        let second = college_data("2021-2022-2", vec![("2021001", "O'Brien", 70.0)]);
+        app_state
+            .set(
+                vec![data, second],
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let term_ids: Vec<i64> =
+            sqlx::query_scalar(r"SELECT term_id FROM terms ORDER BY term_name;")
+                .fetch_all(&app_state.db)
+                .await
+                .unwrap();
+        let major_id: i64 =
+            sqlx::query_scalar(r"SELECT major_id FROM majors WHERE major_name = ?1;")
+                .bind("Computer' Science")
+                .fetch_one(&app_state.db)
+                .await
+                .unwrap();
+
+        let rows = app_state
+            .get_gpa(
+                &term_ids[..1],
+                &RankScope::Major { major_id },
+                2021,
+                &Aggregation::Sum,
+                Cohort::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, hostile);
+        assert_eq!(rows[1].name, "O'Brien");
+        assert_eq!(rows[1].class, "Computer' Science2101");
+        let classes = app_state.get_classes(major_id, 2021).await.unwrap();
+        assert_eq!(classes.len(), 1);
+
+        // the weighted mean binds the term ids and the weights
+        let weights = HashMap::from([(term_ids[0], 1.0), (term_ids[1], 3.0)]);
+        let rows = app_state
+            .get_gpa(
+                &term_ids,
+                &RankScope::Major { major_id },
+                2021,
+                &Aggregation::WeightedMean { weights },
+                Cohort::LatestMajor,
+            )
+            .await
+            .unwrap();
+        let o_brien = rows.iter().find(|row| row.sno == "2021001").unwrap();
+        assert_eq!(o_brien.gpa, Some(72.5));
+
+        let students: i64 = sqlx::query_scalar(r"SELECT COUNT(*) FROM students;")
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+        assert_eq!(students, 2);
+    }
+
+    #[tokio::test]
+    async fn open_accepts_url_characters_in_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data?mode=ro#1.db");
+
+        let app_state = AppState::open(&path).await.unwrap();
+
+        assert!(path.exists());
+        assert!(app_state.get_terms().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn open_splits_classes_merged_across_colleges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+
+        // a database of the versions before the majors and classes were scoped
+        std::fs::File::create(&path).unwrap();
+        let pool = SqlitePool::connect_with(SqliteConnectOptions::new().filename(&path))
+            .await
+            .unwrap();
+        let migrator = sqlx::migrate!("./migrations");
+        let old_migrator = sqlx::migrate::Migrator {
+            migrations: migrator
+                .migrations
+                .iter()
+                .filter(|migration| migration.version < 20261017120000)
+                .cloned()
+                .collect(),
+            ..migrator
+        };
+        old_migrator.run(&pool).await.unwrap();
+        // Computer Science2102 of Engineering was merged into Agronomy, all its students are in
+        // Engineering in the next term; only one of the two students of Computer Science2103 is,
+        // and the only class of Physics can't tell which of it and Software2101 was merged
+        sqlx::query(
+            r"INSERT INTO colleges (college_id, college_name, college_number)
+            VALUES (1, 'Agronomy', '01'), (2, 'Engineering', '02');
+            INSERT INTO majors (major_id, major_name, college_id)
+            VALUES (1, 'Computer Science', 1), (2, 'Software', 2), (3, 'Physics', 1);
+            INSERT INTO classes (class_id, class_name, major_id)
+            VALUES (1, 'Computer Science2101', 1), (2, 'Computer Science2102', 1),
+                (3, 'Software2101', 2), (4, 'Computer Science2103', 1), (5, 'Physics2101', 3);
+            INSERT INTO terms (term_id, term_name) VALUES (1, '2021-2022-1'), (2, '2021-2022-2');
+            INSERT INTO students (student_id, name, student_number)
+            VALUES (1, 'a', '2021001'), (2, 'b', '2021002'), (3, 'c', '2021003'),
+                (4, 'd', '2021004'), (5, 'e', '2021005'), (6, 'f', '2021006'), (7, 'g', '2021007');
+            INSERT INTO academic_records (student_id, term_id, class_id, gpa)
+            VALUES (1, 1, 1, 80.0), (2, 1, 1, 85.0),
+                (3, 1, 2, 70.0), (4, 1, 2, 75.0), (3, 2, 3, 72.0), (4, 2, 3, 77.0),
+                (5, 1, 4, 90.0), (6, 1, 4, 60.0), (5, 2, 3, 91.0),
+                (7, 1, 5, 88.0), (7, 2, 3, 89.0);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+
+        let app_state = AppState::open(&path).await.unwrap();
+
+        let classes: Vec<(String, String, String)> = sqlx::query_as(
+            r"SELECT colleges.college_name, majors.major_name, classes.class_name
+            FROM classes
+            JOIN majors ON majors.major_id = classes.major_id
+            JOIN colleges ON colleges.college_id = majors.college_id
+            ORDER BY classes.class_id;",
+        )
+        .fetch_all(&app_state.db)
+        .await
+        .unwrap();
+        let class = |college: &str, major: &str, class: &str| {
+            (college.to_string(), major.to_string(), class.to_string())
+        };
+        assert_eq!(
+            classes,
+            vec![
+                class("Agronomy", "Computer Science", "Computer Science2101"),
+                class("Engineering", "Computer Science", "Computer Science2102"),
+                class("Engineering", "Software", "Software2101"),
+                class("Agronomy", "Computer Science", "Computer Science2103"),
+                class("Agronomy", "Physics", "Physics2101"),
+            ]
+        );
+
+        let misplaced =
+            |college: &str, class: &str, other: &str, students, other_students| MisplacedClass {
+                college_name: college.to_string(),
+                major_name: class.trim_end_matches(char::is_numeric).to_string(),
+                class_name: class.to_string(),
+                other_college_name: other.to_string(),
+                students,
+                other_students,
+            };
+        assert_eq!(
+            app_state.get_misplaced_classes().await.unwrap(),
+            vec![
+                misplaced("Agronomy", "Computer Science2103", "Engineering", 2, 1),
+                misplaced("Agronomy", "Physics2101", "Engineering", 1, 1),
+                misplaced("Engineering", "Software2101", "Agronomy", 4, 2),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_student_history_by_number_or_name() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let data = vec![
+            college_data(
+                "2021-2022-1",
+                vec![
+                    ("2021001", "张小三", 80.0),
+                    ("2021002", "李四", 90.0),
+                    ("2021003", "王五", 80.0),
+                ],
+            ),
+            college_data("2021-2022-2", vec![("2021001", "张小三", 95.0)]),
+        ];
+        app_state
+            .set(
+                data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+
+        let histories = app_state.get_student_history("2021001").await.unwrap();
+        assert_eq!(histories.len(), 1);
+        let history = &histories[0];
+        assert_eq!(history.student.name, "张小三");
+        assert_eq!(history.terms.len(), 2);
+        let first = &history.terms[0];
+        assert_eq!(first.term_name, "2021-2022-1");
+        assert_eq!(first.class_name, "Computer Science2101");
+        assert_eq!(first.major_name, "Computer Science");
+        assert_eq!(first.college_name, "Engineering");
+        assert_eq!(first.gpa, Some(80.0));
+        assert_eq!(first.class_rank, Some(2));
+        assert_eq!(first.class_size, 3);
+        assert_eq!(first.major_rank, Some(2));
+        assert_eq!(first.major_size, 3);
+        assert_eq!(history.terms[1].class_rank, Some(1));
+        assert_eq!(history.terms[1].class_size, 1);
+
+        // the characters of the name match in order
+        let histories = app_state.get_student_history("张三").await.unwrap();
+        assert_eq!(histories.len(), 1);
+        assert_eq!(histories[0].student.student_number, "2021001");
+
+        // the prefix of the student number matches every student
+        let students = app_state.search_students("2021").await.unwrap();
+        assert_eq!(students.len(), 3);
+
+        // the wildcards of LIKE are matched literally
+        assert!(app_state.search_students("%").await.unwrap().is_empty());
+        assert!(app_state.search_students("_").await.unwrap().is_empty());
+        assert!(app_state.search_students("  ").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_dropped_midway_leaves_database_untouched() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let sids: Vec<String> = (0..2000).map(|i| format!("2021{:04}", i)).collect();
+        let records = sids
+            .iter()
+            .map(|sid| (sid.as_str(), "Alice", 3.5))
+            .collect();
+        let data = vec![college_data("2021-2022-1", records)];
+
+        // drop the import future as soon as it yields, like an aborted task
+        let progress = ProgressReporter::default();
+        let cancel = CancellationToken::new();
+        tokio::select! {
+            biased;
+            _ = tokio::task::yield_now() => {}
+            _ = app_state.set(data, MergeMode::Replace, &progress, &cancel) => {
+                panic!("the import should not finish before being dropped");
+            }
+        }
+
+        assert_eq!(count_records(&app_state).await, 0);
+        let terms = app_state.get_terms().await.unwrap();
+        assert!(terms.is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_cancelled_before_commit_writes_nothing() {
+        let (app_state, _dir) = build_temp_app_state().await.unwrap();
+        let data = || {
+            vec![
+                college_data("2021-2022-1", vec![("2021001", "Alice", 3.5)]),
+                college_data("2021-2022-2", vec![("2021001", "Alice", 3.8)]),
+            ]
+        };
+
+        // cancel when the last college is inserted, only the commit is left
+        let cancel = CancellationToken::new();
+        let progress = {
+            let cancel = cancel.clone();
+            ProgressReporter::new(move |progress| {
+                if progress.total_rows > 0 && progress.inserted_rows == progress.total_rows {
+                    cancel.cancel();
+                }
+            })
+        };
+        let result = app_state
+            .set(data(), MergeMode::Replace, &progress, &cancel)
+            .await;
+        assert!(matches!(result, Err(CustomError::Cancelled)));
+        assert_eq!(count_records(&app_state).await, 0);
+        assert!(app_state.get_terms().await.unwrap().is_empty());
+
+        // cancelling after the import returned doesn't undo it
+        let cancel = CancellationToken::new();
+        app_state
+            .set(
+                data(),
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &cancel,
+            )
+            .await
+            .unwrap();
+        cancel.cancel();
+        assert_eq!(count_records(&app_state).await, 2);
+    }
+
+    #[tokio::test]
+    async fn set_csv_data_handles_empty_data() {
+        let app_state = build_app_state().await.unwrap();
+        let csv_data = vec![];
+
+        let result = app_state
+            .set(
+                csv_data,
+                MergeMode::Replace,
+                &ProgressReporter::default(),
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn get_terms_returns_terms() {
+        let app_state = build_app_state().await.unwrap();
+        let terms = app_state.get_terms().await;
+        assert!(terms.is_ok());
+        assert!(!terms.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_colleges_returns_colleges() {
+        let app_state = build_app_state().await.unwrap();
+        let colleges = app_state.get_colleges().await;
+        assert!(colleges.is_ok());
+        assert!(!colleges.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_majors_returns_majors() {
+        let app_state = build_app_state().await.unwrap();
+        let college_id = 1; // Assuming a valid college_id
+        let majors = app_state.get_majors(college_id).await;
+        assert!(majors.is_ok());
+        assert!(!majors.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_classes_returns_classes() {
+        let app_state = build_app_state().await.unwrap();
+        let major_id = 1; // Assuming a valid major_id
+        let grade = 2021;
+        let classes = app_state.get_classes(major_id, grade).await;
+        assert!(classes.is_ok());
+        assert!(!classes.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_gpa_returns_gpa() {
+        let app_state = build_app_state().await.unwrap();
+        let terms = vec![1, 2]; // Assuming valid term_ids
+        let grade = 2021;
+        let class_id = 1; // Assuming a valid class_id
+
+        let gpa = app_state
+            .get_gpa(
+                &terms,
+                &RankScope::Class { class_id },
+                grade,
+                &Aggregation::Sum,
+                Cohort::default(),
+            )
+            .await;
+        assert!(gpa.is_ok());
+        assert!(!gpa.unwrap().is_empty());
+    }
+}
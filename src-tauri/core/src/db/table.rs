@@ -0,0 +1,164 @@
+use serde::Serialize;
+
+#[derive(sqlx::FromRow, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassInfo {
+    pub class_id: i64,
+    pub class_name: String,
+}
+
+/// 学院相关信息
+#[derive(sqlx::FromRow, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollegeInfo {
+    pub college_id: i64,
+    pub college_name: String,
+}
+
+/// 专业相关信息
+#[derive(sqlx::FromRow, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MajorInfo {
+    pub major_id: i64,
+    pub major_name: String,
+}
+
+/// 学期相关信息
+#[derive(sqlx::FromRow, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TermInfo {
+    pub term_id: i64,
+    pub term_name: String,
+    /// 学年的开始年份, 名称不是`2022-2023-1`形式的学期为空
+    pub start_year: Option<i32>,
+    /// 学年的结束年份
+    pub end_year: Option<i32>,
+    /// 学年中的第几个学期, 3为夏季学期
+    pub semester: Option<i32>,
+}
+
+/// 学生相关信息的id
+
+#[derive(sqlx::FromRow, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultRow {
+    pub class: String,
+    /// 班级所在的专业
+    pub major: String,
+    /// 专业所在的学院
+    pub college: String,
+    pub sno: String,
+    pub name: String,
+    pub gpa: Option<f64>,
+    /// 所选学期中有学分绩, 即参与计算的学期数
+    pub gpa_terms: i64,
+    /// 所选学期中是否有学期缺少学分绩, 此时学分绩只由有学分绩的学期计算
+    #[sqlx(skip)]
+    pub incomplete: bool,
+    /// 按学分绩从高到低排序后的序号, 并列时按学号先后; 学分绩不完整的学生为空
+    #[sqlx(skip)]
+    pub rank: Option<usize>,
+    /// 并列的学生名次相同, 并占用其后的名次, 如 1 2 2 4
+    #[sqlx(skip)]
+    pub competition_rank: Option<usize>,
+    /// 并列的学生名次相同, 且不占用其后的名次, 如 1 2 2 3
+    #[sqlx(skip)]
+    pub dense_rank: Option<usize>,
+    /// 参与排名, 即所选学期学分绩完整的学生数
+    #[sqlx(skip)]
+    pub cohort_size: usize,
+    /// 百分位, 即学分绩不高于该学生的人数占参与排名人数的百分比
+    #[sqlx(skip)]
+    pub percentile: Option<f64>,
+    /// 转入或转出所查询的范围, 范围内的专业之间的转专业不计; 仅按最新专业或原专业排名时计算
+    #[sqlx(skip)]
+    pub transfer: Option<Transfer>,
+    /// 转入前或转出后所在的专业
+    #[sqlx(skip)]
+    pub transfer_major: Option<String>,
+    /// 不属于排名的群体, 如按最新专业排名时已转出的学生, 只列出而不参与排名
+    #[sqlx(skip)]
+    pub outside_cohort: bool,
+}
+
+/// 转专业的方向
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Transfer {
+    /// 从其他专业转入
+    In,
+    /// 转出到其他专业
+    Out,
+}
+
+/// 学生的基本信息
+#[derive(sqlx::FromRow, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StudentInfo {
+    pub student_id: i64,
+    pub student_number: String,
+    pub name: String,
+}
+
+/// 学生某一学期的班级、专业、学院、学分绩与排名
+#[derive(sqlx::FromRow, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TermRecord {
+    pub term_id: i64,
+    pub term_name: String,
+    pub class_name: String,
+    pub major_name: String,
+    pub college_name: String,
+    pub gpa: Option<f64>,
+    /// 在班级中的名次, 并列的学生名次相同; 没有学分绩时为空
+    pub class_rank: Option<i64>,
+    /// 班级中有学分绩的学生数
+    pub class_size: i64,
+    /// 在同年级同专业中的名次, 并列的学生名次相同; 没有学分绩时为空
+    pub major_rank: Option<i64>,
+    /// 同年级同专业中有学分绩的学生数
+    pub major_size: i64,
+}
+
+/// 学生各学期的成绩记录
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StudentHistory {
+    #[serde(flatten)]
+    pub student: StudentInfo,
+    pub terms: Vec<TermRecord>,
+}
+
+/// 学生在相邻两个学期之间的班级变动, 如转专业、大类分流或调班
+#[derive(sqlx::FromRow, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassChange {
+    pub student_number: String,
+    pub name: String,
+    /// 变动前的学期
+    pub from_term_name: String,
+    /// 发生变动的学期, 即变动后的第一个学期
+    pub term_id: i64,
+    pub term_name: String,
+    pub from_class: String,
+    pub to_class: String,
+    pub from_major: String,
+    pub to_major: String,
+    /// 专业是否变化, 为`false`时只是同专业内调班
+    pub major_changed: bool,
+}
+
+/// 多数学生在其他学期属于另一个学院的班级, 可能是旧版本按名称合并不同学院的同名专业所致
+#[derive(sqlx::FromRow, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MisplacedClass {
+    pub college_name: String,
+    pub major_name: String,
+    pub class_name: String,
+    /// 学生在其他学期所属的学院
+    pub other_college_name: String,
+    /// 班级的学生数
+    pub students: i64,
+    /// 其中在其他学期属于该学院的学生数
+    pub other_students: i64,
+}
@@ -0,0 +1,140 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+/// 自定义错误类型
+///
+/// 序列化为`{ code, message, details }`返回给前端, 见[`ErrorCode`]
+#[derive(thiserror::Error, Debug)]
+pub enum CustomError {
+    /// 文件读取失败
+    #[error("读取文件失败: {0}")]
+    FileReadError(#[from] std::io::Error),
+    /// 无法解析或非法的文件
+    #[error("非法的文件: {0}")]
+    IllegalFileError(String),
+    /// 不符合预期的文件,如csv文件名称不符合预期
+    #[error("不符合预期的文件: {0}")]
+    UnexpectedFileError(String),
+    /// csv处理错误
+    #[error("解析csv失败: {0}")]
+    CsvParseError(#[from] csv::Error),
+    /// csv文件中的数据不符合预期
+    #[error("csv数据错误: {0}")]
+    CsvDataError(String),
+    /// zip压缩包处理错误
+    #[error("解析压缩包失败: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+    /// regex相关错误
+    #[error("failed to parse or compile a regular expression: {0}")]
+    RegexError(#[from] regex::Error),
+    /// 有目录或文件解析失败, 未导入任何数据
+    #[error("{}个目录或文件解析失败, 未导入任何数据", .0.len())]
+    ImportFailed(Vec<String>),
+    /// 导出xlsx文件失败
+    #[error("导出xlsx失败: {0}")]
+    XlsxError(#[from] rust_xlsxwriter::XlsxError),
+    /// 导出json文件失败
+    #[error("导出json失败: {0}")]
+    JsonError(#[from] serde_json::Error),
+    /// 数据库读写失败
+    #[error("数据库错误: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+    /// 数据库迁移失败
+    #[error("数据库迁移失败: {0}")]
+    MigrateError(#[from] sqlx::migrate::MigrateError),
+    /// 参数不合法, 如缺少权重或路径不存在
+    #[error("{0}")]
+    ValidationError(String),
+    /// 导入被取消
+    #[error("导入已取消")]
+    Cancelled,
+    /// 用户取消了文件或文件夹的选择
+    #[error("{0}")]
+    SelectionCancelled(String),
+    /// 未知错误
+    #[error("未知错误: {0}")]
+    UnknownError(String),
+}
+
+/// 错误的类别, 前端据此决定如何提示
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCode {
+    /// 数据库读写或迁移失败
+    Database,
+    /// 文件读写失败
+    Io,
+    /// 文件、csv或压缩包解析失败
+    Parse,
+    /// 参数不合法
+    Validation,
+    /// 导入或选择被取消
+    Cancelled,
+    /// 未知错误
+    Unknown,
+}
+
+impl CustomError {
+    /// 错误的类别
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CustomError::DatabaseError(_) | CustomError::MigrateError(_) => ErrorCode::Database,
+            CustomError::FileReadError(_)
+            | CustomError::XlsxError(_)
+            | CustomError::JsonError(_) => ErrorCode::Io,
+            CustomError::IllegalFileError(_)
+            | CustomError::UnexpectedFileError(_)
+            | CustomError::CsvParseError(_)
+            | CustomError::CsvDataError(_)
+            | CustomError::ZipError(_)
+            | CustomError::RegexError(_)
+            | CustomError::ImportFailed(_) => ErrorCode::Parse,
+            CustomError::ValidationError(_) => ErrorCode::Validation,
+            CustomError::Cancelled | CustomError::SelectionCancelled(_) => ErrorCode::Cancelled,
+            CustomError::UnknownError(_) => ErrorCode::Unknown,
+        }
+    }
+
+    /// 错误的详细信息, 如解析失败的文件列表或底层错误的调试信息
+    pub fn details(&self) -> Option<String> {
+        match self {
+            CustomError::ImportFailed(failures) => Some(failures.join("\n")),
+            CustomError::FileReadError(e) => Some(format!("{:?}", e)),
+            CustomError::CsvParseError(e) => Some(format!("{:?}", e)),
+            CustomError::ZipError(e) => Some(format!("{:?}", e)),
+            CustomError::DatabaseError(e) => Some(format!("{:?}", e)),
+            CustomError::MigrateError(e) => Some(format!("{:?}", e)),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for CustomError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CustomError", 3)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_error() {
+        let error = CustomError::ImportFailed(vec!["a.csv: 解析失败".to_string()]);
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "parse");
+        assert_eq!(json["message"], "1个目录或文件解析失败, 未导入任何数据");
+        assert_eq!(json["details"], "a.csv: 解析失败");
+
+        let json = serde_json::to_value(CustomError::Cancelled).unwrap();
+        assert_eq!(json["code"], "cancelled");
+        assert_eq!(json["details"], serde_json::Value::Null);
+
+        let error = CustomError::from(sqlx::Error::RowNotFound);
+        assert_eq!(error.code(), ErrorCode::Database);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::db::table::ResultRow;
+use crate::err::CustomError;
+use rust_xlsxwriter::Workbook;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::Write, path::Path};
+
+/// UTF-8的BOM, 使中文Windows上的Excel按UTF-8打开csv文件
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 导出表格的表头
+const HEADER: [&str; 7] = ["名次", "学院", "专业", "班级", "学号", "姓名", "学分绩"];
+
+/// 导出的文件格式
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// 带BOM的UTF-8编码csv文件
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl ExportFormat {
+    /// 根据文件的扩展名确定格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// 导出的一行, 即一个学生的排名
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ExportRow<'row> {
+    /// 并列的学生名次相同; 不参与排名的学生为空
+    rank: Option<usize>,
+    college: &'row str,
+    major: &'row str,
+    class: &'row str,
+    sno: &'row str,
+    name: &'row str,
+    gpa: Option<f64>,
+}
+
+impl<'row> From<&'row ResultRow> for ExportRow<'row> {
+    fn from(row: &'row ResultRow) -> Self {
+        Self {
+            rank: row.competition_rank,
+            college: &row.college,
+            major: &row.major,
+            class: &row.class,
+            sno: &row.sno,
+            name: &row.name,
+            gpa: row.gpa,
+        }
+    }
+}
+
+/// 将排名导出到文件, 已存在的文件会被覆盖
+///
+/// # Arguments
+///
+/// * `rows` - 已排名的查询结果, 按查询结果的顺序导出
+/// * `path` - 导出的文件路径
+/// * `format` - 导出的文件格式
+///
+/// # Errors
+///
+/// 如果文件写入失败, 返回`CustomError::FileReadError`
+/// 如果生成文件内容失败, 返回对应格式的错误
+pub fn export_rows(
+    rows: &[ResultRow],
+    path: &Path,
+    format: ExportFormat,
+) -> Result<(), CustomError> {
+    let rows: Vec<ExportRow> = rows.iter().map(ExportRow::from).collect();
+    match format {
+        ExportFormat::Csv => write_csv(&rows, path),
+        ExportFormat::Xlsx => write_xlsx(&rows, path),
+        ExportFormat::Json => {
+            let file = File::create(path)?;
+            serde_json::to_writer_pretty(file, &rows)?;
+            Ok(())
+        }
+    }
+}
+
+/// 写入带BOM的csv文件, 缺失的名次与学分绩为空
+fn write_csv(rows: &[ExportRow], path: &Path) -> Result<(), CustomError> {
+    let mut file = File::create(path)?;
+    file.write_all(UTF8_BOM)?;
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(HEADER)?;
+    for row in rows {
+        writer.write_record([
+            row.rank.map_or(String::new(), |rank| rank.to_string()),
+            row.college.to_string(),
+            row.major.to_string(),
+            row.class.to_string(),
+            row.sno.to_string(),
+            row.name.to_string(),
+            row.gpa.map_or(String::new(), |gpa| gpa.to_string()),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 写入xlsx文件, 名次与学分绩为数字单元格, 学号为文本以保留前导零
+fn write_xlsx(rows: &[ExportRow], path: &Path) -> Result<(), CustomError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    for (col, title) in HEADER.iter().enumerate() {
+        worksheet.write_string(0, col as u16, *title)?;
+    }
+    for (i, row) in rows.iter().enumerate() {
+        let r = i as u32 + 1;
+        if let Some(rank) = row.rank {
+            worksheet.write_number(r, 0, rank as f64)?;
+        }
+        worksheet.write_string(r, 1, row.college)?;
+        worksheet.write_string(r, 2, row.major)?;
+        worksheet.write_string(r, 3, row.class)?;
+        worksheet.write_string(r, 4, row.sno)?;
+        worksheet.write_string(r, 5, row.name)?;
+        if let Some(gpa) = row.gpa {
+            worksheet.write_number(r, 6, gpa)?;
+        }
+    }
+    workbook.save(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn rows() -> Vec<ResultRow> {
+        vec![
+            ResultRow {
+                class: "计算机科学2101".to_string(),
+                major: "计算机科学".to_string(),
+                college: "工程学院".to_string(),
+                sno: "2021001".to_string(),
+                name: "张三".to_string(),
+                gpa: Some(90.5),
+                competition_rank: Some(1),
+                ..Default::default()
+            },
+            ResultRow {
+                class: "计算机科学2101".to_string(),
+                major: "计算机科学".to_string(),
+                college: "工程学院".to_string(),
+                sno: "2021002".to_string(),
+                name: "李四".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_export_csv_with_bom() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ranking.csv");
+
+        export_rows(&rows(), &path, ExportFormat::Csv).unwrap();
+
+        let content = std::fs::read(&path).unwrap();
+        assert!(content.starts_with(UTF8_BOM));
+        let text = String::from_utf8(content[UTF8_BOM.len()..].to_vec()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "名次,学院,专业,班级,学号,姓名,学分绩");
+        assert_eq!(
+            lines[1],
+            "1,工程学院,计算机科学,计算机科学2101,2021001,张三,90.5"
+        );
+        assert_eq!(
+            lines[2],
+            ",工程学院,计算机科学,计算机科学2101,2021002,李四,"
+        );
+    }
+
+    #[test]
+    fn test_export_json_and_xlsx() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ranking.json");
+        export_rows(&rows(), &path, ExportFormat::Json).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[0]["rank"], 1);
+        assert_eq!(json[0]["sno"], "2021001");
+        assert_eq!(json[1]["gpa"], serde_json::Value::Null);
+
+        // xlsx is a zip archive
+        let path = dir.path().join("ranking.xlsx");
+        export_rows(&rows(), &path, ExportFormat::Xlsx).unwrap();
+        assert!(std::fs::read(&path).unwrap().starts_with(b"PK"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("a/ranking.XLSX")),
+            Some(ExportFormat::Xlsx)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("ranking.txt")), None);
+        assert_eq!(ExportFormat::from_path(Path::new("ranking")), None);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::data_parser::{DataConsumer, DataProducer};
+use crate::db::{AppState, MergeMode};
+use crate::err::CustomError;
+use crate::progress::ProgressReporter;
+use crate::report::ImportReport;
+use std::path::PathBuf;
+use tokio_util::sync::CancellationToken;
+
+/// run the producer/consumer pipeline on the directories or zip archives and store the result
+pub async fn run_import(
+    db: &AppState,
+    paths: Vec<PathBuf>,
+    mode: MergeMode,
+    progress: &ProgressReporter,
+    cancel: &CancellationToken,
+) -> Result<ImportReport, CustomError> {
+    if let Some(path) = paths.iter().find(|path| !path.exists()) {
+        return Err(CustomError::ValidationError(format!(
+            "路径不存在: {}",
+            path.display()
+        )));
+    }
+
+    // parse csv
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
+    let producer = DataProducer::new(tx)
+        .with_progress(progress.clone())
+        .with_cancellation(cancel.clone());
+    let mut consumer = DataConsumer::new(rx).with_progress(progress.clone());
+
+    let producer_task = tokio::spawn(async move {
+        let mut report = ImportReport::default();
+        for path in paths {
+            report.extend(producer.produce(path).await?);
+        }
+        Ok::<_, CustomError>(report)
+    });
+    let consumer_task = tokio::spawn(async move { consumer.consume().await });
+
+    let (producer_result, consumer_result) = tokio::join!(producer_task, consumer_task);
+
+    // handle the error, the tasks only fail to join if they panicked
+    let mut report = producer_result
+        .map_err(|e| CustomError::UnknownError(format!("解析任务异常退出: {}", e)))??;
+    let data = consumer_result
+        .map_err(|e| CustomError::UnknownError(format!("接收任务异常退出: {}", e)))?;
+
+    // the import is all-or-nothing, so nothing is written if any file could not be parsed
+    let failures = report.failures();
+    if !failures.is_empty() {
+        return Err(CustomError::ImportFailed(
+            failures
+                .iter()
+                .map(|(path, error)| format!("{}: {}", path, error))
+                .collect(),
+        ));
+    }
+
+    // set db, the transaction is rolled back if the import is cancelled before the commit
+    let result = db.set(data, mode, progress, cancel).await?;
+    report.apply_insert_result(result);
+    progress.finished();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// create a data directory with one class of the term
+    fn data_dir(root: &std::path::Path, term_name: &str) -> PathBuf {
+        let dir = root.join(term_name);
+        let college_dir = dir
+            .join(format!("{}学期智育学分绩", term_name))
+            .join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+        fs::write(
+            college_dir.join("a21major2101hz.csv"),
+            format!(
+                "xh,xm,k101\n,,00231|0.0|{}智育学分绩||\n2021001,Alice,85.5\n2021002,Bob,80.0\n",
+                term_name
+            ),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn run_import_imports_all_paths() {
+        let temp_dir = tempdir().unwrap();
+        let db = AppState::open(&temp_dir.path().join("data.db"))
+            .await
+            .unwrap();
+        let paths = vec![
+            data_dir(temp_dir.path(), "2021-2022-1"),
+            data_dir(temp_dir.path(), "2021-2022-2"),
+        ];
+
+        let report = run_import(
+            &db,
+            paths,
+            MergeMode::Replace,
+            &ProgressReporter::default(),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.imported_rows(), 4);
+        assert_eq!(report.terms.len(), 2);
+        assert_eq!(db.get_terms().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn run_import_rejects_missing_path() {
+        let temp_dir = tempdir().unwrap();
+        let db = AppState::open(&temp_dir.path().join("data.db"))
+            .await
+            .unwrap();
+        let paths = vec![
+            data_dir(temp_dir.path(), "2021-2022-1"),
+            temp_dir.path().join("missing"),
+        ];
+
+        let result = run_import(
+            &db,
+            paths,
+            MergeMode::Replace,
+            &ProgressReporter::default(),
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(db.get_terms().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::{Mutex, MutexGuard};
+use tokio_util::sync::CancellationToken;
+
+/// 正在进行的导入任务, 同一时间只允许一个导入任务
+#[derive(Default)]
+pub struct ImportJobs {
+    current: Mutex<Option<CancellationToken>>,
+}
+
+/// 导入任务的句柄, 离开作用域时任务结束
+pub struct ImportJob<'jobs> {
+    jobs: &'jobs ImportJobs,
+    token: CancellationToken,
+}
+
+impl ImportJobs {
+    /// 开始新的导入任务, 若已有导入任务正在进行则返回None
+    pub fn start(&self) -> Option<ImportJob<'_>> {
+        let mut current = self.lock();
+        if current.is_some() {
+            return None;
+        }
+        let token = CancellationToken::new();
+        *current = Some(token.clone());
+        Some(ImportJob { jobs: self, token })
+    }
+
+    /// 锁定当前任务; 持有锁的线程panic时锁中的令牌仍然有效, 因此忽略锁的中毒
+    fn lock(&self) -> MutexGuard<'_, Option<CancellationToken>> {
+        self.current
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 取消正在进行的导入任务, 返回是否有任务被取消
+    pub fn cancel(&self) -> bool {
+        let current = self.lock();
+        match &*current {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl ImportJob<'_> {
+    /// 用于通知生产者与数据库任务停止的令牌
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for ImportJob<'_> {
+    fn drop(&mut self) {
+        *self.jobs.lock() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_one_job_at_a_time() {
+        let jobs = ImportJobs::default();
+        let job = jobs.start().unwrap();
+        assert!(jobs.start().is_none());
+
+        drop(job);
+        assert!(jobs.start().is_some());
+    }
+
+    #[test]
+    fn test_cancel_job() {
+        let jobs = ImportJobs::default();
+        assert!(!jobs.cancel());
+
+        let job = jobs.start().unwrap();
+        assert!(!job.token().is_cancelled());
+        assert!(jobs.cancel());
+        assert!(job.token().is_cancelled());
+    }
+}
@@ -0,0 +1,25 @@
+//! the core of NEAU-GPA-Getter, shared by the desktop app and the command-line tool
+//!
+//! it parses the published gpa csv files, stores them in a SQLite database and queries the
+//! ranking, without depending on Tauri
+
+pub mod csv_processor;
+pub mod data_parser;
+pub mod db;
+pub mod err;
+pub mod export;
+pub mod import;
+pub mod job;
+pub mod progress;
+pub mod ranking;
+pub mod report;
+pub mod statistics;
+pub mod term;
+pub mod validator;
+
+pub use db::{Aggregation, AppState, Cohort, MergeMode, RankScope, TermPreset};
+pub use err::CustomError;
+pub use import::run_import;
+pub use job::ImportJobs;
+pub use progress::ProgressReporter;
+pub use report::ImportReport;
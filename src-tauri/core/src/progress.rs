@@ -0,0 +1,238 @@
+use serde::Serialize;
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+/// 导入进度事件名称
+pub const IMPORT_PROGRESS_EVENT: &str = "import-progress";
+
+/// 导入所处的阶段
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportStage {
+    /// 正在查找学院目录
+    Discovering,
+    /// 正在解析csv文件
+    Parsing,
+    /// 正在写入数据库
+    Inserting,
+    /// 导入结束
+    Finished,
+}
+
+/// 导入进度, 每次事件都携带完整的累计信息
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    /// 当前阶段
+    pub stage: ImportStage,
+    /// 当前处理的学期
+    pub term_name: Option<String>,
+    /// 当前处理的学院
+    pub college_name: Option<String>,
+    /// 已发现的学院目录数
+    pub discovered_dirs: usize,
+    /// 已解析的学院目录数
+    pub parsed_dirs: usize,
+    /// 已解析的csv文件数
+    pub parsed_files: usize,
+    /// 已写入的记录数
+    pub inserted_rows: usize,
+    /// 需要写入的记录总数
+    pub total_rows: usize,
+    /// 当前阶段预计剩余的秒数
+    pub eta_secs: Option<f64>,
+}
+
+impl Default for ImportProgress {
+    fn default() -> Self {
+        Self {
+            stage: ImportStage::Discovering,
+            term_name: None,
+            college_name: None,
+            discovered_dirs: 0,
+            parsed_dirs: 0,
+            parsed_files: 0,
+            inserted_rows: 0,
+            total_rows: 0,
+            eta_secs: None,
+        }
+    }
+}
+
+type ProgressCallback = dyn Fn(ImportProgress) + Send + Sync;
+
+struct ReporterState {
+    progress: ImportProgress,
+    stage_started: Instant,
+}
+
+/// 导入进度的汇报者, 可在生产者、消费者与数据库任务之间共享
+///
+/// 默认的汇报者不会发出任何事件
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    inner: Option<Arc<(Mutex<ReporterState>, Box<ProgressCallback>)>>,
+}
+
+impl ProgressReporter {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(ImportProgress) + Send + Sync + 'static,
+    {
+        let state = ReporterState {
+            progress: ImportProgress::default(),
+            stage_started: Instant::now(),
+        };
+        Self {
+            inner: Some(Arc::new((Mutex::new(state), Box::new(callback)))),
+        }
+    }
+
+    /// 已发现一个数据目录或压缩包中的全部学院目录, 开始解析
+    ///
+    /// 同时导入多个路径时, 发现的目录数会累加
+    pub fn dirs_discovered(&self, count: usize) {
+        self.update(|state| {
+            if state.progress.stage != ImportStage::Parsing {
+                state.enter(ImportStage::Parsing);
+            }
+            state.progress.discovered_dirs += count;
+        });
+    }
+
+    /// 一个学院目录解析完毕
+    pub fn college_parsed(&self, term_name: &str, college_name: &str, file_count: usize) {
+        self.update(|state| {
+            let progress = &mut state.progress;
+            progress.term_name = Some(term_name.to_string());
+            progress.college_name = Some(college_name.to_string());
+            progress.parsed_dirs += 1;
+            progress.parsed_files += file_count;
+            let (done, total) = (progress.parsed_dirs, progress.discovered_dirs);
+            state.estimate(done, total);
+        });
+    }
+
+    /// 解析完毕, 开始写入数据库
+    pub fn insert_started(&self, total_rows: usize) {
+        self.update(|state| {
+            state.enter(ImportStage::Inserting);
+            state.progress.total_rows = total_rows;
+        });
+    }
+
+    /// 一个学院的记录写入完毕
+    pub fn rows_inserted(&self, term_name: &str, college_name: &str, row_count: usize) {
+        self.update(|state| {
+            let progress = &mut state.progress;
+            progress.term_name = Some(term_name.to_string());
+            progress.college_name = Some(college_name.to_string());
+            progress.inserted_rows += row_count;
+            let (done, total) = (progress.inserted_rows, progress.total_rows);
+            state.estimate(done, total);
+        });
+    }
+
+    /// 导入结束
+    pub fn finished(&self) {
+        self.update(|state| {
+            state.enter(ImportStage::Finished);
+            state.progress.eta_secs = Some(0.0);
+        });
+    }
+
+    /// 更新进度并发出事件
+    fn update(&self, f: impl FnOnce(&mut ReporterState)) {
+        if let Some(inner) = &self.inner {
+            let (state, callback) = &**inner;
+            let progress = {
+                // a panic while holding the lock leaves the progress usable, so the poisoning
+                // doesn't take the import down
+                let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                f(&mut state);
+                state.progress.clone()
+            };
+            callback(progress);
+        }
+    }
+}
+
+impl ReporterState {
+    fn enter(&mut self, stage: ImportStage) {
+        self.progress.stage = stage;
+        self.progress.eta_secs = None;
+        self.stage_started = Instant::now();
+    }
+
+    /// 按当前阶段已用时间线性估计剩余时间
+    fn estimate(&mut self, done: usize, total: usize) {
+        self.progress.eta_secs = match done {
+            0 => None,
+            _ => {
+                let elapsed = self.stage_started.elapsed().as_secs_f64();
+                Some(elapsed * total.saturating_sub(done) as f64 / done as f64)
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_reporter_accumulates() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = Arc::clone(&events);
+        let reporter = ProgressReporter::new(move |progress| {
+            events_clone.lock().unwrap().push(progress);
+        });
+
+        reporter.dirs_discovered(2);
+        reporter.college_parsed("2022-2023-1", "College", 3);
+        reporter.college_parsed("2022-2023-1", "Other", 2);
+        reporter.insert_started(10);
+        reporter.rows_inserted("2022-2023-1", "College", 10);
+        reporter.finished();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[2].stage, ImportStage::Parsing);
+        assert_eq!(events[2].parsed_dirs, 2);
+        assert_eq!(events[2].parsed_files, 5);
+        assert_eq!(events[2].eta_secs, Some(0.0));
+        assert_eq!(events[4].inserted_rows, 10);
+        assert_eq!(events[4].college_name.as_deref(), Some("College"));
+        assert_eq!(events[5].stage, ImportStage::Finished);
+    }
+
+    #[test]
+    fn test_progress_serializes_to_camel_case() {
+        let value = serde_json::to_value(ImportProgress::default()).unwrap();
+        assert_eq!(value["stage"], "discovering");
+        assert_eq!(value["discoveredDirs"], 0);
+        assert!(value["etaSecs"].is_null());
+    }
+
+    #[test]
+    fn test_progress_reporter_survives_poisoned_lock() {
+        let reporter = ProgressReporter::new(|_| {});
+        let inner = Arc::clone(reporter.inner.as_ref().unwrap());
+        let _ = std::thread::spawn(move || {
+            let _guard = inner.0.lock().unwrap();
+            panic!("poison the progress lock");
+        })
+        .join();
+
+        reporter.finished();
+    }
+
+    #[test]
+    fn test_default_reporter_is_silent() {
+        let reporter = ProgressReporter::default();
+        reporter.dirs_discovered(1);
+        reporter.finished();
+    }
+}
@@ -0,0 +1,156 @@
+use crate::db::table::ResultRow;
+use std::cmp::Ordering;
+
+/// 学分绩相差不超过该值时视为并列, 以免多学期求和的浮点误差拆散并列的学生
+const TIE_EPSILON: f64 = 1e-6;
+
+/// 按学分绩从高到低排序, 并计算每个学生的名次、并列名次与百分位
+///
+/// 没有学分绩的学生排在最后, 名次与百分位均为空。`rank_incomplete`为`false`时,
+/// 如按学期求和, 所选学期学分绩不完整的学生同样不参与排名, 按已有学分绩排在完整的学生之后。
+/// 不属于排名群体的学生也不参与排名
+pub fn rank_rows(rows: &mut [ResultRow], rank_incomplete: bool) {
+    let is_ranked = |row: &ResultRow| {
+        row.gpa.is_some() && (rank_incomplete || !row.incomplete) && !row.outside_cohort
+    };
+    rows.sort_by(|a, b| {
+        is_ranked(b)
+            .cmp(&is_ranked(a))
+            .then_with(|| match (a.gpa, b.gpa) {
+                (Some(x), Some(y)) => y.total_cmp(&x),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| a.sno.cmp(&b.sno))
+    });
+
+    let cohort_size = rows.iter().filter(|row| is_ranked(row)).count();
+    let mut previous_gpa: Option<f64> = None;
+    let mut competition_rank = 0;
+    let mut dense_rank = 0;
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.cohort_size = cohort_size;
+        let gpa = match row.gpa {
+            Some(gpa) if is_ranked(row) => gpa,
+            _ => continue,
+        };
+        let tied = previous_gpa.is_some_and(|previous| (previous - gpa).abs() <= TIE_EPSILON);
+        if !tied {
+            competition_rank = i + 1;
+            dense_rank += 1;
+        }
+        previous_gpa = Some(gpa);
+
+        row.rank = Some(i + 1);
+        row.competition_rank = Some(competition_rank);
+        row.dense_rank = Some(dense_rank);
+        row.percentile =
+            Some((cohort_size - competition_rank + 1) as f64 / cohort_size as f64 * 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(sno: &str, gpa: Option<f64>) -> ResultRow {
+        ResultRow {
+            class: "Computer Science2101".to_string(),
+            sno: sno.to_string(),
+            name: sno.to_string(),
+            gpa,
+            incomplete: gpa.is_none(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rank_rows_ties() {
+        let mut rows = vec![
+            row("2021004", Some(80.0)),
+            row("2021002", Some(85.0)),
+            row("2021001", Some(90.0)),
+            row("2021003", Some(85.0)),
+        ];
+
+        rank_rows(&mut rows, false);
+
+        let snos: Vec<&str> = rows.iter().map(|row| row.sno.as_str()).collect();
+        assert_eq!(snos, vec!["2021001", "2021002", "2021003", "2021004"]);
+        let ranks = |f: fn(&ResultRow) -> Option<usize>| rows.iter().map(f).collect::<Vec<_>>();
+        assert_eq!(ranks(|r| r.rank), vec![Some(1), Some(2), Some(3), Some(4)]);
+        assert_eq!(
+            ranks(|r| r.competition_rank),
+            vec![Some(1), Some(2), Some(2), Some(4)]
+        );
+        assert_eq!(
+            ranks(|r| r.dense_rank),
+            vec![Some(1), Some(2), Some(2), Some(3)]
+        );
+        assert_eq!(rows[0].percentile, Some(100.0));
+        assert_eq!(rows[1].percentile, Some(75.0));
+        assert_eq!(rows[3].percentile, Some(25.0));
+    }
+
+    #[test]
+    fn test_rank_rows_floating_point_ties() {
+        let mut rows = vec![row("2021001", Some(0.1 + 0.2)), row("2021002", Some(0.3))];
+
+        rank_rows(&mut rows, false);
+
+        assert_eq!(rows[0].competition_rank, rows[1].competition_rank);
+    }
+
+    #[test]
+    fn test_rank_rows_missing_gpa() {
+        let mut rows = vec![
+            row("2021001", None),
+            row("2021002", Some(0.0)),
+            row("2021003", Some(60.0)),
+        ];
+
+        rank_rows(&mut rows, false);
+
+        assert_eq!(rows[0].sno, "2021003");
+        assert_eq!(rows[1].competition_rank, Some(2));
+        assert_eq!(rows[2].sno, "2021001");
+        assert_eq!(rows[2].rank, None);
+        assert_eq!(rows[2].percentile, None);
+        assert!(rows.iter().all(|row| row.cohort_size == 2));
+    }
+
+    #[test]
+    fn test_rank_rows_incomplete_terms() {
+        let mut incomplete = row("2021001", Some(95.0));
+        incomplete.incomplete = true;
+        let mut rows = vec![
+            incomplete,
+            row("2021002", None),
+            row("2021003", Some(170.0)),
+            row("2021004", Some(160.0)),
+        ];
+
+        rank_rows(&mut rows, false);
+
+        let snos: Vec<&str> = rows.iter().map(|row| row.sno.as_str()).collect();
+        assert_eq!(snos, vec!["2021003", "2021004", "2021001", "2021002"]);
+        assert_eq!(rows[1].competition_rank, Some(2));
+        assert_eq!(rows[2].competition_rank, None);
+        assert_eq!(rows[2].gpa, Some(95.0));
+        assert!(rows.iter().all(|row| row.cohort_size == 2));
+    }
+
+    #[test]
+    fn test_rank_rows_ranks_incomplete_terms() {
+        let mut incomplete = row("2021001", Some(95.0));
+        incomplete.incomplete = true;
+        let mut rows = vec![incomplete, row("2021002", Some(85.0))];
+
+        rank_rows(&mut rows, true);
+
+        assert_eq!(rows[0].sno, "2021001");
+        assert_eq!(rows[0].competition_rank, Some(1));
+        assert!(rows.iter().all(|row| row.cohort_size == 2));
+    }
+}
@@ -0,0 +1,265 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 目录或csv文件的导入结果
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum ImportOutcome {
+    /// 已导入
+    Imported,
+    /// 已跳过, 如名称不符合规范或学期已导入
+    Skipped { reason: String },
+    /// 导入失败
+    Failed { error: String },
+}
+
+/// 学院目录的导入结果
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryReport {
+    pub path: String,
+    pub term_name: Option<String>,
+    pub college_name: Option<String>,
+    pub outcome: ImportOutcome,
+}
+
+/// csv文件的导入结果
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    pub path: String,
+    pub term_name: Option<String>,
+    pub college_name: Option<String>,
+    pub class_name: Option<String>,
+    /// 文件中的记录数
+    pub rows: usize,
+    pub outcome: ImportOutcome,
+}
+
+/// 班级导入的记录数
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassSummary {
+    pub class_name: String,
+    pub rows: usize,
+}
+
+/// 学院导入的记录数
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CollegeSummary {
+    pub college_name: String,
+    pub rows: usize,
+    pub classes: Vec<ClassSummary>,
+}
+
+/// 学期导入的记录数
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TermSummary {
+    pub term_name: String,
+    pub rows: usize,
+    pub colleges: Vec<CollegeSummary>,
+}
+
+/// 写入数据库的结果
+#[derive(Debug, Default)]
+pub struct InsertResult {
+    /// 因已导入而跳过的学期
+    pub skipped_terms: Vec<String>,
+}
+
+/// 一次导入的完整报告
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// 所有被检查过的学院目录
+    pub directories: Vec<DirectoryReport>,
+    /// 所有被检查过的csv文件
+    pub files: Vec<FileReport>,
+    /// 因已导入而跳过的学期
+    pub skipped_terms: Vec<String>,
+    /// 按学期、学院、班级统计的导入记录数
+    pub terms: Vec<TermSummary>,
+}
+
+impl ImportReport {
+    /// 合并另一部分报告
+    pub fn extend(&mut self, other: ImportReport) {
+        self.directories.extend(other.directories);
+        self.files.extend(other.files);
+    }
+
+    /// 解析失败的目录与文件, 及其错误信息
+    pub fn failures(&self) -> Vec<(&str, &str)> {
+        self.directories
+            .iter()
+            .map(|dir| (&dir.path, &dir.outcome))
+            .chain(self.files.iter().map(|file| (&file.path, &file.outcome)))
+            .filter_map(|(path, outcome)| match outcome {
+                ImportOutcome::Failed { error } => Some((path.as_str(), error.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 根据写入数据库的结果更新目录与文件的导入结果, 并统计各学期的记录数
+    pub fn apply_insert_result(&mut self, result: InsertResult) {
+        let outcomes = self
+            .directories
+            .iter_mut()
+            .map(|dir| (&dir.term_name, &mut dir.outcome))
+            .chain(
+                self.files
+                    .iter_mut()
+                    .map(|file| (&file.term_name, &mut file.outcome)),
+            );
+        for (term_name, outcome) in outcomes {
+            if *outcome != ImportOutcome::Imported {
+                continue;
+            }
+            let term_name = term_name.as_deref().unwrap_or_default();
+            if result.skipped_terms.iter().any(|term| term == term_name) {
+                *outcome = ImportOutcome::Skipped {
+                    reason: format!("学期{}已导入", term_name),
+                };
+            }
+        }
+        self.skipped_terms = result.skipped_terms;
+        self.summarize();
+    }
+
+    /// 已导入的记录总数
+    pub fn imported_rows(&self) -> usize {
+        self.terms.iter().map(|term| term.rows).sum()
+    }
+
+    /// 统计已导入文件的记录数
+    fn summarize(&mut self) {
+        let mut terms: BTreeMap<&str, BTreeMap<&str, BTreeMap<&str, usize>>> = BTreeMap::new();
+        for file in &self.files {
+            if file.outcome != ImportOutcome::Imported {
+                continue;
+            }
+            *terms
+                .entry(file.term_name.as_deref().unwrap_or_default())
+                .or_default()
+                .entry(file.college_name.as_deref().unwrap_or_default())
+                .or_default()
+                .entry(file.class_name.as_deref().unwrap_or_default())
+                .or_default() += file.rows;
+        }
+
+        self.terms = terms
+            .into_iter()
+            .map(|(term_name, colleges)| {
+                let colleges: Vec<CollegeSummary> = colleges
+                    .into_iter()
+                    .map(|(college_name, classes)| {
+                        let classes: Vec<ClassSummary> = classes
+                            .into_iter()
+                            .map(|(class_name, rows)| ClassSummary {
+                                class_name: class_name.to_string(),
+                                rows,
+                            })
+                            .collect();
+                        CollegeSummary {
+                            college_name: college_name.to_string(),
+                            rows: classes.iter().map(|c| c.rows).sum(),
+                            classes,
+                        }
+                    })
+                    .collect();
+                TermSummary {
+                    term_name: term_name.to_string(),
+                    rows: colleges.iter().map(|c| c.rows).sum(),
+                    colleges,
+                }
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(term_name: &str, college_name: &str, class_name: &str, rows: usize) -> FileReport {
+        FileReport {
+            path: format!("{}/{}/{}.csv", term_name, college_name, class_name),
+            term_name: Some(term_name.to_string()),
+            college_name: Some(college_name.to_string()),
+            class_name: Some(class_name.to_string()),
+            rows,
+            outcome: ImportOutcome::Imported,
+        }
+    }
+
+    #[test]
+    fn test_apply_insert_result() {
+        let mut report = ImportReport {
+            files: vec![
+                file("2022-2023-1", "College", "major2201", 30),
+                file("2022-2023-1", "College", "major2202", 28),
+                file("2022-2023-2", "College", "major2201", 30),
+                file("2022-2023-2", "Other", "other2201", 25),
+            ],
+            ..Default::default()
+        };
+
+        report.apply_insert_result(InsertResult::default());
+
+        assert_eq!(report.imported_rows(), 113);
+        assert_eq!(report.terms.len(), 2);
+        assert_eq!(report.terms[0].rows, 58);
+        assert_eq!(report.terms[0].colleges[0].classes.len(), 2);
+        assert_eq!(report.terms[1].colleges.len(), 2);
+        assert!(report.failures().is_empty());
+    }
+
+    #[test]
+    fn test_failures() {
+        let mut failed = file("2022-2023-1", "College", "major2202", 0);
+        failed.outcome = ImportOutcome::Failed {
+            error: "empty file".to_string(),
+        };
+        let report = ImportReport {
+            files: vec![file("2022-2023-1", "College", "major2201", 30), failed],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            report.failures(),
+            vec![("2022-2023-1/College/major2202.csv", "empty file")]
+        );
+    }
+
+    #[test]
+    fn test_apply_insert_result_skipped_terms() {
+        let mut report = ImportReport {
+            files: vec![file("2022-2023-1", "College", "major2201", 30)],
+            ..Default::default()
+        };
+
+        report.apply_insert_result(InsertResult {
+            skipped_terms: vec!["2022-2023-1".to_string()],
+        });
+
+        assert_eq!(report.imported_rows(), 0);
+        assert_eq!(report.skipped_terms, vec!["2022-2023-1"]);
+        assert!(matches!(
+            report.files[0].outcome,
+            ImportOutcome::Skipped { .. }
+        ));
+    }
+
+    #[test]
+    fn test_outcome_serialization() {
+        let value = serde_json::to_value(ImportOutcome::Skipped {
+            reason: "name".to_string(),
+        })
+        .unwrap();
+        assert_eq!(value["status"], "skipped");
+        assert_eq!(value["reason"], "name");
+    }
+}
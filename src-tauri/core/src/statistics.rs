@@ -0,0 +1,183 @@
+use crate::db::table::ResultRow;
+use crate::err::CustomError;
+use serde::Serialize;
+
+/// 直方图默认的分组数
+pub const DEFAULT_BINS: usize = 10;
+
+/// 查询结果中参与排名学生学分绩的统计信息
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortStatistics {
+    /// 参与排名, 即有可比较学分绩的学生数
+    pub count: usize,
+    /// 缺少学分绩的学生数, 包括没有学分绩与按学期求和时学期不完整的学生
+    pub missing: usize,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    /// 总体标准差
+    pub std_dev: Option<f64>,
+    /// 下四分位数, 与Excel的`QUARTILE.INC`相同, 按线性插值计算
+    pub q1: Option<f64>,
+    /// 上四分位数
+    pub q3: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub histogram: Vec<HistogramBin>,
+}
+
+/// 直方图的分组, 包含下界, 不包含上界; 最后一组同时包含上界
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+}
+
+/// 统计查询结果中参与排名学生的学分绩
+///
+/// 不属于排名群体的学生(如按最新专业排名时已转出的学生)不计入统计
+///
+/// # Arguments
+///
+/// * `rows` - 已排名的查询结果, 见[`rank_rows`](crate::ranking::rank_rows)
+/// * `bins` - 直方图在最低分与最高分之间等分的组数
+///
+/// # Errors
+///
+/// `bins`为0时返回`CustomError::ValidationError`
+pub fn summarize(rows: &[ResultRow], bins: usize) -> Result<CohortStatistics, CustomError> {
+    if bins == 0 {
+        return Err(CustomError::ValidationError(
+            "直方图的分组数必须大于0".to_string(),
+        ));
+    }
+    let rows: Vec<&ResultRow> = rows.iter().filter(|row| !row.outside_cohort).collect();
+    let mut values: Vec<f64> = rows
+        .iter()
+        .filter(|row| row.competition_rank.is_some())
+        .filter_map(|row| row.gpa)
+        .collect();
+    values.sort_by(f64::total_cmp);
+
+    let mut statistics = CohortStatistics {
+        count: values.len(),
+        missing: rows.len() - values.len(),
+        ..Default::default()
+    };
+    let (Some(&min), Some(&max)) = (values.first(), values.last()) else {
+        return Ok(statistics);
+    };
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    statistics.mean = Some(mean);
+    statistics.std_dev = Some(variance.sqrt());
+    statistics.median = Some(quantile(&values, 0.5));
+    statistics.q1 = Some(quantile(&values, 0.25));
+    statistics.q3 = Some(quantile(&values, 0.75));
+    statistics.min = Some(min);
+    statistics.max = Some(max);
+    statistics.histogram = histogram(&values, min, max, bins);
+    Ok(statistics)
+}
+
+/// 已排序数据的分位数, 在相邻的两个值之间线性插值
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = (sorted.len() - 1) as f64 * q;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// 在最低分与最高分之间等分出`bins`组, 统计每组的人数
+///
+/// 所有学分绩相同时只有一组
+fn histogram(sorted: &[f64], min: f64, max: f64, bins: usize) -> Vec<HistogramBin> {
+    if min == max {
+        return vec![HistogramBin {
+            lower: min,
+            upper: max,
+            count: sorted.len(),
+        }];
+    }
+    let width = (max - min) / bins as f64;
+    let mut histogram: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            lower: min + width * i as f64,
+            upper: if i + 1 == bins {
+                max
+            } else {
+                min + width * (i + 1) as f64
+            },
+            count: 0,
+        })
+        .collect();
+    for value in sorted {
+        let i = (((value - min) / width) as usize).min(bins - 1);
+        histogram[i].count += 1;
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ranking::rank_rows;
+
+    fn rows(gpas: &[Option<f64>]) -> Vec<ResultRow> {
+        let mut rows: Vec<ResultRow> = gpas
+            .iter()
+            .enumerate()
+            .map(|(i, gpa)| ResultRow {
+                sno: format!("2021{:03}", i),
+                gpa: *gpa,
+                gpa_terms: gpa.is_some() as i64,
+                incomplete: gpa.is_none(),
+                ..Default::default()
+            })
+            .collect();
+        rank_rows(&mut rows, false);
+        rows
+    }
+
+    #[test]
+    fn test_summarize() {
+        let rows = rows(&[Some(70.0), Some(90.0), None, Some(80.0), Some(60.0)]);
+
+        let statistics = summarize(&rows, 3).unwrap();
+        assert_eq!(statistics.count, 4);
+        assert_eq!(statistics.missing, 1);
+        assert_eq!(statistics.mean, Some(75.0));
+        assert_eq!(statistics.median, Some(75.0));
+        assert_eq!(statistics.q1, Some(67.5));
+        assert_eq!(statistics.q3, Some(82.5));
+        assert_eq!(statistics.min, Some(60.0));
+        assert_eq!(statistics.max, Some(90.0));
+        assert!((statistics.std_dev.unwrap() - 125f64.sqrt()).abs() < 1e-9);
+        let counts: Vec<usize> = statistics.histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 1, 2]);
+        assert_eq!(statistics.histogram[0].lower, 60.0);
+        assert_eq!(statistics.histogram[2].upper, 90.0);
+    }
+
+    #[test]
+    fn test_summarize_empty_and_equal() {
+        let statistics = summarize(&rows(&[None]), DEFAULT_BINS).unwrap();
+        assert_eq!(statistics.count, 0);
+        assert_eq!(statistics.missing, 1);
+        assert_eq!(statistics.mean, None);
+        assert!(statistics.histogram.is_empty());
+
+        let statistics = summarize(&rows(&[Some(80.0), Some(80.0)]), DEFAULT_BINS).unwrap();
+        assert_eq!(statistics.std_dev, Some(0.0));
+        assert_eq!(statistics.histogram.len(), 1);
+        assert_eq!(statistics.histogram[0].count, 2);
+    }
+
+    #[test]
+    fn test_summarize_zero_bins() {
+        assert!(summarize(&rows(&[Some(80.0)]), 0).is_err());
+    }
+}
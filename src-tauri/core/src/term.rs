@@ -0,0 +1,66 @@
+use crate::err::CustomError;
+use regex::Regex;
+
+/// 学期名称, 如`2022-2023-1`
+const TERM_NAME_PATTERN: &str = r"^(\d{4})-(\d{4})-([123])$";
+
+/// 由学期名称解析出的学年与学期, 按时间先后排序
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Term {
+    /// 学年的开始年份
+    pub start_year: i32,
+    /// 学年的结束年份
+    pub end_year: i32,
+    /// 学年中的第几个学期, 1为秋季学期, 2为春季学期, 3为夏季学期
+    pub semester: i32,
+}
+
+impl Term {
+    /// 解析学期名称, 如`2022-2023-1`或夏季学期`2022-2023-3`
+    ///
+    /// # Errors
+    ///
+    /// 如果名称不符合规范或学年不是连续的两年, 返回`CustomError::UnexpectedFileError`
+    pub fn parse(term_name: &str) -> Result<Self, CustomError> {
+        let re = Regex::new(TERM_NAME_PATTERN)?;
+        let illegal =
+            || CustomError::UnexpectedFileError(format!("{} 学期名称不符合要求", term_name));
+        let captures = re.captures(term_name).ok_or_else(illegal)?;
+        // the captures are all digits
+        let number = |i: usize| captures[i].parse::<i32>().map_err(|_| illegal());
+        let term = Term {
+            start_year: number(1)?,
+            end_year: number(2)?,
+            semester: number(3)?,
+        };
+        if term.end_year != term.start_year + 1 {
+            return Err(illegal());
+        }
+        Ok(term)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_term() {
+        let term = Term::parse("2022-2023-1").unwrap();
+        assert_eq!(term.start_year, 2022);
+        assert_eq!(term.end_year, 2023);
+        assert_eq!(term.semester, 1);
+
+        // the summer term comes after the spring term of the same academic year
+        let summer = Term::parse("2022-2023-3").unwrap();
+        assert!(Term::parse("2022-2023-2").unwrap() < summer);
+        assert!(summer < Term::parse("2023-2024-1").unwrap());
+    }
+
+    #[test]
+    fn test_parse_illegal_term() {
+        for term_name in ["2022-2023-4", "2022-2024-1", "2022-2023", "2022-2023-1学期"] {
+            assert!(Term::parse(term_name).is_err(), "{}", term_name);
+        }
+    }
+}
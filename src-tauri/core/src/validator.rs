@@ -0,0 +1,317 @@
+use super::data_parser::{CollegeData, DataConsumer, DataProducer};
+use super::err::CustomError;
+use super::report::{ImportOutcome, ImportReport};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+    path::PathBuf,
+};
+
+/// 默认的合法绩点范围
+pub const DEFAULT_GPA_RANGE: RangeInclusive<f64> = 0.0..=100.0;
+
+/// 数据检查中发现的问题
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum ValidationIssue {
+    /// 目录名称不符合学院目录格式
+    #[serde(rename_all = "camelCase")]
+    SkippedDirectory { path: String },
+    /// 学院目录无法解析, 如学期目录名称不符合规范
+    #[serde(rename_all = "camelCase")]
+    InvalidDirectory { path: String, error: String },
+    /// 文件名称不符合班级成绩文件格式
+    #[serde(rename_all = "camelCase")]
+    SkippedFile { path: String },
+    /// csv文件无法解析, 如空文件
+    #[serde(rename_all = "camelCase")]
+    InvalidFile { path: String, error: String },
+    /// csv文件中没有学生记录
+    #[serde(rename_all = "camelCase")]
+    EmptyFile {
+        term_name: String,
+        class_name: String,
+    },
+    /// csv文件中没有绩点列
+    #[serde(rename_all = "camelCase")]
+    MissingGpaColumn {
+        term_name: String,
+        class_name: String,
+    },
+    /// 同一学期中学号重复出现
+    #[serde(rename_all = "camelCase")]
+    DuplicateStudent {
+        term_name: String,
+        student_number: String,
+        class_names: Vec<String>,
+    },
+    /// 绩点超出合法范围
+    #[serde(rename_all = "camelCase")]
+    GpaOutOfRange {
+        term_name: String,
+        class_name: String,
+        student_number: String,
+        gpa: f64,
+    },
+}
+
+/// 数据检查的结果
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    /// 检查过的学院目录数
+    pub directories: usize,
+    /// 检查过的csv文件数
+    pub files: usize,
+    /// 检查过的学生记录数
+    pub records: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// 数据是否可以放心导入
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// 检查数据目录或压缩包, 不会写入数据库
+///
+/// # Arguments
+///
+/// * `path` - the data directory or the `data_yyyy_yyyy.zip` archive
+/// * `gpa_range` - the valid range of the gpa
+///
+/// # Errors
+///
+/// 如果数据目录或压缩包无法读取，返回对应的`CustomError`
+pub async fn validate(
+    path: PathBuf,
+    gpa_range: RangeInclusive<f64>,
+) -> Result<ValidationReport, CustomError> {
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
+    let producer = DataProducer::new(tx);
+    let mut consumer = DataConsumer::new(rx);
+
+    let producer_task = tokio::spawn(async move { producer.produce(path).await });
+    let consumer_task = tokio::spawn(async move { consumer.consume().await });
+    let (producer_result, consumer_result) = tokio::join!(producer_task, consumer_task);
+
+    let report = producer_result.map_err(|e| CustomError::UnknownError(e.to_string()))??;
+    let data = consumer_result.map_err(|e| CustomError::UnknownError(e.to_string()))?;
+
+    Ok(check(&report, &data, &gpa_range))
+}
+
+/// 根据解析结果与解析出的数据检查问题
+fn check(
+    report: &ImportReport,
+    data: &[CollegeData],
+    gpa_range: &RangeInclusive<f64>,
+) -> ValidationReport {
+    let mut result = ValidationReport {
+        directories: report.directories.len(),
+        files: report.files.len(),
+        ..Default::default()
+    };
+
+    for dir in &report.directories {
+        match &dir.outcome {
+            ImportOutcome::Skipped { .. } => {
+                result.issues.push(ValidationIssue::SkippedDirectory {
+                    path: dir.path.clone(),
+                })
+            }
+            ImportOutcome::Failed { error } => {
+                result.issues.push(ValidationIssue::InvalidDirectory {
+                    path: dir.path.clone(),
+                    error: error.clone(),
+                })
+            }
+            ImportOutcome::Imported => {}
+        }
+    }
+    for file in &report.files {
+        match &file.outcome {
+            ImportOutcome::Skipped { .. } => result.issues.push(ValidationIssue::SkippedFile {
+                path: file.path.clone(),
+            }),
+            ImportOutcome::Failed { error } => result.issues.push(ValidationIssue::InvalidFile {
+                path: file.path.clone(),
+                error: error.clone(),
+            }),
+            ImportOutcome::Imported => {}
+        }
+    }
+
+    // the classes of each student number in each term
+    let mut students: BTreeMap<(&str, &str), BTreeSet<&str>> = BTreeMap::new();
+    let mut occurrences: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for college_data in data {
+        let term_name = college_data.term_name.as_str();
+        for table in &college_data.data {
+            let class_name = table.class_name.as_str();
+            result.records += table.records.len();
+            if table.records.is_empty() {
+                result.issues.push(ValidationIssue::EmptyFile {
+                    term_name: term_name.to_string(),
+                    class_name: class_name.to_string(),
+                });
+            }
+            if !table.has_gpa_column {
+                result.issues.push(ValidationIssue::MissingGpaColumn {
+                    term_name: term_name.to_string(),
+                    class_name: class_name.to_string(),
+                });
+            }
+            for record in &table.records {
+                let key = (term_name, record.sid.as_str());
+                students.entry(key).or_default().insert(class_name);
+                *occurrences.entry(key).or_default() += 1;
+                match record.gpa {
+                    Some(gpa) if !gpa_range.contains(&gpa) => {
+                        result.issues.push(ValidationIssue::GpaOutOfRange {
+                            term_name: term_name.to_string(),
+                            class_name: class_name.to_string(),
+                            student_number: record.sid.clone(),
+                            gpa,
+                        })
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    for ((term_name, student_number), count) in occurrences {
+        if count > 1 {
+            let class_names = students[&(term_name, student_number)]
+                .iter()
+                .map(|class_name| class_name.to_string())
+                .collect();
+            result.issues.push(ValidationIssue::DuplicateStudent {
+                term_name: term_name.to_string(),
+                student_number: student_number.to_string(),
+                class_names,
+            });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const HEADER: &str = "xh,xm,k101\n,,00231|0.0|2021-2022-1智育学分绩||\n";
+
+    #[tokio::test]
+    async fn test_validate_reports_problems() {
+        let temp_dir = tempdir().unwrap();
+        let term_dir = temp_dir.path().join("2021-2022-1学期智育学分绩");
+        let college_dir = term_dir.join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+        fs::create_dir_all(temp_dir.path().join("2021-2022-1").join("22Other")).unwrap();
+
+        fs::write(
+            college_dir.join("a21major2101hz.csv"),
+            format!("{}2021001,Alice,85.5\n2021002,Bob,120.0\n", HEADER),
+        )
+        .unwrap();
+        fs::write(
+            college_dir.join("a21major2102hz.csv"),
+            format!("{}2021001,Alice,85.5\n", HEADER),
+        )
+        .unwrap();
+        fs::write(
+            college_dir.join("a21major2103hz.csv"),
+            "xh,xm\n,\n2021003,Carol\n",
+        )
+        .unwrap();
+        fs::write(college_dir.join("a21major2104hz.csv"), "").unwrap();
+
+        let report = validate(temp_dir.path().to_path_buf(), DEFAULT_GPA_RANGE)
+            .await
+            .unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(report.records, 4);
+        let has_issue = |f: &dyn Fn(&ValidationIssue) -> bool| report.issues.iter().any(f);
+        assert!(has_issue(&|i| matches!(
+            i,
+            ValidationIssue::InvalidDirectory { .. }
+        )));
+        assert!(has_issue(&|i| matches!(
+            i,
+            ValidationIssue::InvalidFile { .. }
+        )));
+        assert!(has_issue(&|i| matches!(
+            i,
+            ValidationIssue::MissingGpaColumn { .. }
+        )));
+        assert!(has_issue(&|i| matches!(
+            i,
+            ValidationIssue::GpaOutOfRange { gpa, .. } if *gpa == 120.0
+        )));
+        assert!(has_issue(&|i| matches!(
+            i,
+            ValidationIssue::DuplicateStudent { student_number, class_names, .. }
+                if student_number == "2021001" && class_names.len() == 2
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_validate_clean_data() {
+        let temp_dir = tempdir().unwrap();
+        let college_dir = temp_dir
+            .path()
+            .join("2021-2022-1学期智育学分绩")
+            .join("21College");
+        fs::create_dir_all(&college_dir).unwrap();
+        fs::write(
+            college_dir.join("a21major2101hz.csv"),
+            format!("{}2021001,Alice,85.5\n2021002,Bob,80.0\n", HEADER),
+        )
+        .unwrap();
+
+        let report = validate(temp_dir.path().to_path_buf(), DEFAULT_GPA_RANGE)
+            .await
+            .unwrap();
+
+        assert!(report.is_valid());
+        assert_eq!(report.directories, 1);
+        assert_eq!(report.files, 1);
+        assert_eq!(report.records, 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_zip_archive() {
+        use std::io::Write;
+
+        let temp_dir = tempdir().unwrap();
+        let zip_path = temp_dir.path().join("data_2021_2022.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        writer
+            .start_file(
+                "data_2021_2022/2021-2022-1学期智育学分绩/21College/a21major2101hz.csv",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+        writer
+            .write_all(format!("{}2021001,Alice,85.5\n2021002,Bob,120.0\n", HEADER).as_bytes())
+            .unwrap();
+        writer.finish().unwrap();
+
+        let report = validate(zip_path, DEFAULT_GPA_RANGE).await.unwrap();
+
+        assert_eq!(report.files, 1);
+        assert_eq!(report.records, 2);
+        assert!(matches!(
+            &report.issues[..],
+            [ValidationIssue::GpaOutOfRange { student_number, .. }] if student_number == "2021002"
+        ));
+    }
+}
@@ -1,123 +1,235 @@
-use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
-};
+use std::path::PathBuf;
 
-use db::AppState;
-use tauri::{AppHandle, Manager};
-use tauri_plugin_dialog::DialogExt;
-
-mod csv_processor;
-mod data_parser;
-mod db;
-mod err;
+use neau_gpa_core::db::{self, Aggregation, AppState, Cohort, MergeMode, RankScope, TermPreset};
+use neau_gpa_core::export::{export_rows, ExportFormat};
+pub use neau_gpa_core::job::ImportJobs;
+use neau_gpa_core::progress::{ProgressReporter, IMPORT_PROGRESS_EVENT};
+use neau_gpa_core::statistics::{self, CohortStatistics};
+use neau_gpa_core::{run_import, validator, CustomError, ImportReport};
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, FilePath};
 
+/// open the database under the app data directory and manage it as the app state
 pub async fn setup_db(app: &AppHandle) {
-    let db = AppState::build(app)
+    let mut path = app.path().data_dir().expect("Failed to get data directory");
+
+    // append the database file name to the path
+    path.push("com.neau.gpa.getter");
+    if !path.exists() {
+        std::fs::create_dir_all(&path).expect("Failed to create data directory");
+    }
+    path.push("data.db");
+
+    let db = AppState::open(&path)
         .await
         .expect("Failed to build the database");
     app.manage(db);
 }
 
-async fn pick_folder_dialog(app: AppHandle) -> Option<PathBuf> {
-    let directory_path = Arc::new(Mutex::new(None));
+/// run the blocking dialog on another thread, return the selected path
+///
+/// # Errors
+///
+/// return `CustomError::SelectionCancelled` with the message if nothing is selected
+async fn run_dialog(
+    dialog: impl FnOnce() -> Option<FilePath> + Send + 'static,
+    cancelled_message: &str,
+) -> Result<PathBuf, CustomError> {
+    let selected_path = tokio::task::spawn_blocking(dialog)
+        .await
+        .map_err(|e| CustomError::UnknownError(format!("对话框异常退出: {}", e)))?;
+    match selected_path {
+        Some(path) => Ok(PathBuf::from(path.to_string())),
+        None => Err(CustomError::SelectionCancelled(
+            cancelled_message.to_string(),
+        )),
+    }
+}
 
-    // clone the directory_path which will be used in closure
-    let directory_path_clone = Arc::clone(&directory_path);
+async fn pick_folder_dialog(app: AppHandle) -> Result<PathBuf, CustomError> {
+    run_dialog(
+        move || app.dialog().file().blocking_pick_folder(),
+        "取消选择文件夹",
+    )
+    .await
+}
 
-    // spawn a new async thread
-    let handle = tokio::spawn(async move {
-        let selected_path = app.dialog().file().blocking_pick_folder();
-        let mut dir = directory_path_clone
-            .lock()
-            .expect("Failed to lock directory path when selecting directory");
-        *dir = selected_path;
-    });
+async fn pick_archive_dialog(app: AppHandle) -> Result<PathBuf, CustomError> {
+    run_dialog(
+        move || {
+            app.dialog()
+                .file()
+                .add_filter("zip", &["zip"])
+                .blocking_pick_file()
+        },
+        "取消选择压缩包",
+    )
+    .await
+}
 
-    // await the task
-    handle
-        .await
-        .expect("Failed to await the selecting directory task");
-
-    // get the value
-    let dir_path = directory_path
-        .lock()
-        .expect("Failed to lock directory path when getting the value");
-    match &*dir_path {
-        Some(path) => Some(PathBuf::from(path.to_string())),
-        None => None,
-    }
+async fn save_file_dialog(app: AppHandle) -> Result<PathBuf, CustomError> {
+    run_dialog(
+        move || {
+            app.dialog()
+                .file()
+                .add_filter("csv", &["csv"])
+                .add_filter("xlsx", &["xlsx"])
+                .add_filter("json", &["json"])
+                .set_file_name("排名.csv")
+                .blocking_save_file()
+        },
+        "取消导出",
+    )
+    .await
 }
 
 #[tauri::command]
 pub async fn initialize_searcher(
     db: tauri::State<'_, AppState>,
+    jobs: tauri::State<'_, ImportJobs>,
     app: AppHandle,
-) -> Result<String, String> {
-    let path: PathBuf = match pick_folder_dialog(app).await {
-        Some(val) => val,
-        None => return Err("取消选择文件夹".to_string()),
-    };
+    mode: Option<MergeMode>,
+) -> Result<ImportReport, CustomError> {
+    let path = pick_folder_dialog(app.clone()).await?;
 
-    // parse csv
-    let (tx, rx) = tokio::sync::mpsc::channel(100);
-    let producer = data_parser::DataProducer::new(tx);
-    let mut consumer = data_parser::DataConsumer::new(rx);
+    import_data(&db, &jobs, &app, vec![path], mode.unwrap_or_default()).await
+}
 
-    let producer_task = tokio::spawn(async move {
-        producer.produce(path).await.unwrap();
-    });
-    let consumer_task = tokio::spawn(async move { consumer.consume().await });
+/// import the data from the `data_yyyy_yyyy.zip` archive without unzipping it
+#[tauri::command]
+pub async fn import_archive(
+    db: tauri::State<'_, AppState>,
+    jobs: tauri::State<'_, ImportJobs>,
+    app: AppHandle,
+    mode: Option<MergeMode>,
+) -> Result<ImportReport, CustomError> {
+    let path = pick_archive_dialog(app.clone()).await?;
 
-    let (producer_result, consumer_result) = tokio::join!(producer_task, consumer_task);
+    import_data(&db, &jobs, &app, vec![path], mode.unwrap_or_default()).await
+}
 
-    // handle the error
-    if let Err(e) = producer_result {
-        return Err(format!("Failed to produce data: {:?}", e));
+/// import the data from the given directories or `data_yyyy_yyyy.zip` archives
+///
+/// all the paths are imported together, so either all of them land or none of them does
+#[tauri::command]
+pub async fn import_paths(
+    db: tauri::State<'_, AppState>,
+    jobs: tauri::State<'_, ImportJobs>,
+    app: AppHandle,
+    paths: Vec<PathBuf>,
+    mode: Option<MergeMode>,
+) -> Result<ImportReport, CustomError> {
+    if paths.is_empty() {
+        return Err(CustomError::ValidationError(
+            "未指定要导入的路径".to_string(),
+        ));
     }
-    let data = match consumer_result {
-        Ok(t) => t,
-        Err(e) => return Err(format!("Failed to consume data: {:?}", e)),
+
+    import_data(&db, &jobs, &app, paths, mode.unwrap_or_default()).await
+}
+
+/// run the import as the current job
+///
+/// the progress is emitted to the front end as the `import-progress` event,
+/// and the import can be stopped by `cancel_import` with nothing written to the database
+async fn import_data(
+    db: &AppState,
+    jobs: &ImportJobs,
+    app: &AppHandle,
+    paths: Vec<PathBuf>,
+    mode: MergeMode,
+) -> Result<ImportReport, CustomError> {
+    let job = match jobs.start() {
+        Some(job) => job,
+        None => {
+            return Err(CustomError::ValidationError(
+                "已有导入任务正在进行".to_string(),
+            ))
+        }
     };
 
-    // set db
-    let result = db
-        .set(data)
-        .await
-        .map_err(|e| format!("Failed to set data: {:?}", e))?;
+    let emitter = app.clone();
+    let progress = ProgressReporter::new(move |payload| {
+        if let Err(e) = emitter.emit(IMPORT_PROGRESS_EVENT, payload) {
+            log::warn!("Failed to emit the import progress: {:?}", e);
+        }
+    });
 
-    Ok(result)
+    run_import(db, paths, mode, &progress, job.token()).await
+}
+
+/// cancel the running import, return whether there was an import to cancel
+#[tauri::command]
+pub fn cancel_import(jobs: tauri::State<'_, ImportJobs>) -> bool {
+    jobs.cancel()
+}
+
+/// check the data directory before importing it, nothing is written to the database
+#[tauri::command]
+pub async fn validate_data(
+    app: AppHandle,
+    min_gpa: Option<f64>,
+    max_gpa: Option<f64>,
+) -> Result<validator::ValidationReport, CustomError> {
+    let path = pick_folder_dialog(app).await?;
+
+    validate_path(path, min_gpa, max_gpa).await
+}
+
+/// check the `data_yyyy_yyyy.zip` archive before importing it, nothing is written to the database
+#[tauri::command]
+pub async fn validate_archive(
+    app: AppHandle,
+    min_gpa: Option<f64>,
+    max_gpa: Option<f64>,
+) -> Result<validator::ValidationReport, CustomError> {
+    let path = pick_archive_dialog(app).await?;
+
+    validate_path(path, min_gpa, max_gpa).await
+}
+
+/// check the data directory or archive with the gpa range, the default range if not given
+async fn validate_path(
+    path: PathBuf,
+    min_gpa: Option<f64>,
+    max_gpa: Option<f64>,
+) -> Result<validator::ValidationReport, CustomError> {
+    let gpa_range = min_gpa.unwrap_or(*validator::DEFAULT_GPA_RANGE.start())
+        ..=max_gpa.unwrap_or(*validator::DEFAULT_GPA_RANGE.end());
+    validator::validate(path, gpa_range).await
 }
 
 #[tauri::command]
 pub async fn get_terms(
     app: tauri::State<'_, AppState>,
-) -> Result<Vec<db::table::TermInfo>, String> {
-    match app.get_terms().await {
-        Ok(terms) => Ok(terms),
-        Err(e) => Err(format!("Failed to get terms: {:?}", e)),
-    }
+) -> Result<Vec<db::table::TermInfo>, CustomError> {
+    app.get_terms().await
 }
 
 #[tauri::command]
 pub async fn get_colleges(
     app: tauri::State<'_, AppState>,
-) -> Result<Vec<db::table::CollegeInfo>, String> {
-    match app.get_colleges().await {
-        Ok(colleges) => Ok(colleges),
-        Err(e) => Err(format!("Failed to get colleges: {:?}", e)),
-    }
+) -> Result<Vec<db::table::CollegeInfo>, CustomError> {
+    app.get_colleges().await
 }
 
 #[tauri::command]
 pub async fn get_majors(
     app: tauri::State<'_, AppState>,
     college_id: i64,
-) -> Result<Vec<db::table::MajorInfo>, String> {
-    match app.get_majors(college_id).await {
-        Ok(majors) => Ok(majors),
-        Err(e) => Err(format!("Failed to get majors: {:?}", e)),
-    }
+) -> Result<Vec<db::table::MajorInfo>, CustomError> {
+    app.get_majors(college_id).await
+}
+
+/// get the grades, i.e. the enrollment years, that exist under the major, or under any major
+/// if it is not given
+#[tauri::command]
+pub async fn get_grades(
+    app: tauri::State<'_, AppState>,
+    major_id: Option<i64>,
+) -> Result<Vec<i32>, CustomError> {
+    app.get_grades(major_id).await
 }
 
 #[tauri::command]
@@ -125,23 +237,113 @@ pub async fn get_classes(
     app: tauri::State<'_, AppState>,
     major_id: i64,
     grade: i32,
-) -> Result<Vec<db::table::ClassInfo>, String> {
-    match app.get_classes(major_id, grade).await {
-        Ok(classes) => Ok(classes),
-        Err(e) => Err(format!("Failed to get classes: {:?}", e)),
-    }
+) -> Result<Vec<db::table::ClassInfo>, CustomError> {
+    app.get_classes(major_id, grade).await
 }
 
+/// resolve the explicitly selected terms and the preset to the term ids in chronological order
 #[tauri::command]
-pub async fn get_gpa(
+pub async fn resolve_terms(
     app: tauri::State<'_, AppState>,
     terms: Vec<i64>,
-    major_id: i64,
-    grade: String,
-    class_id: Option<i64>,
-) -> Result<Vec<db::table::ResultRow>, String> {
-    match app.get_gpa(&terms, major_id, &grade, class_id).await {
-        Ok(gpa) => Ok(gpa),
-        Err(e) => Err(format!("Failed to get gpa: {:?}", e)),
+    preset: Option<TermPreset>,
+    grade: Option<i32>,
+) -> Result<Vec<i64>, CustomError> {
+    app.resolve_terms(&terms, preset, grade).await
+}
+
+/// the selection of a gpa ranking, shared by the ranking, the export and the statistics
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaQuery {
+    /// the term ids selected explicitly
+    #[serde(default)]
+    terms: Vec<i64>,
+    /// the preset selecting more terms, such as an academic year
+    preset: Option<TermPreset>,
+    /// the students ranked together, such as a class, a major or a college
+    scope: RankScope,
+    grade: i32,
+    aggregation: Option<Aggregation>,
+    cohort: Option<Cohort>,
+}
+
+impl GpaQuery {
+    /// resolve the terms of the query and get the ranked rows
+    async fn rows(self, db: &AppState) -> Result<Vec<db::table::ResultRow>, CustomError> {
+        let terms = db
+            .resolve_terms(&self.terms, self.preset, Some(self.grade))
+            .await?;
+        db.get_gpa(
+            &terms,
+            &self.scope,
+            self.grade,
+            &self.aggregation.unwrap_or_default(),
+            self.cohort.unwrap_or_default(),
+        )
+        .await
     }
 }
+
+/// get the gpa ranking of the selected terms, which can be given by a preset
+#[tauri::command]
+pub async fn get_gpa(
+    app: tauri::State<'_, AppState>,
+    query: GpaQuery,
+) -> Result<Vec<db::table::ResultRow>, CustomError> {
+    query.rows(&app).await
+}
+
+/// export the gpa ranking of the query to a csv, xlsx or json file chosen by the user,
+/// the format is given by the extension of the file
+#[tauri::command]
+pub async fn export_gpa(
+    db: tauri::State<'_, AppState>,
+    app: AppHandle,
+    query: GpaQuery,
+) -> Result<String, CustomError> {
+    let path = save_file_dialog(app).await?;
+    let format = ExportFormat::from_path(&path).ok_or(CustomError::ValidationError(format!(
+        "不支持的文件格式: {}",
+        path.display()
+    )))?;
+
+    let rows = query.rows(&db).await?;
+    export_rows(&rows, &path, format)?;
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+pub async fn get_statistics(
+    app: tauri::State<'_, AppState>,
+    query: GpaQuery,
+    bins: Option<usize>,
+) -> Result<CohortStatistics, CustomError> {
+    let rows = query.rows(&app).await?;
+    statistics::summarize(&rows, bins.unwrap_or(statistics::DEFAULT_BINS))
+}
+
+#[tauri::command]
+pub async fn get_student_history(
+    app: tauri::State<'_, AppState>,
+    keyword: String,
+) -> Result<Vec<db::table::StudentHistory>, CustomError> {
+    app.get_student_history(&keyword).await
+}
+
+#[tauri::command]
+pub async fn get_class_changes(
+    app: tauri::State<'_, AppState>,
+    major_id: Option<i64>,
+    grade: Option<i32>,
+) -> Result<Vec<db::table::ClassChange>, CustomError> {
+    app.get_class_changes(major_id, grade).await
+}
+
+/// the classes possibly merged from another college by the old versions
+#[tauri::command]
+pub async fn get_misplaced_classes(
+    app: tauri::State<'_, AppState>,
+) -> Result<Vec<db::table::MisplacedClass>, CustomError> {
+    app.get_misplaced_classes().await
+}
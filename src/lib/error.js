@@ -0,0 +1,11 @@
+// the commands reject with `{ code, message, details }`,
+// the details of a parse error list the files that failed
+export function errorMessage(error) {
+    if (typeof error === "string") {
+        return error;
+    }
+    if (error.code === "parse" && error.details) {
+        return `${error.message}:\n${error.details}`;
+    }
+    return error.message;
+}
@@ -1,3 +1,5 @@
 import {writable} from "svelte/store";
 
-export const tableData = writable([]);
\ No newline at end of file
+export const tableData = writable([]);
+// the arguments of the last gpa query, shared by the statistics
+export const lastQuery = writable(null);